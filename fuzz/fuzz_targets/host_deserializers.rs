@@ -0,0 +1,34 @@
+//! Deserializes RPC responses and converts them into a `SharesProof`.
+#![no_main]
+
+use alloy_primitives::{B256, U256};
+use libfuzzer_sys::fuzz_target;
+use openvm_blobstream::{
+    host::{GetTx, MerkleProof, RowProof, ShareProof, TxProof},
+    verifier::{BinaryMerkleProof, SharesProof},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Responses {
+    tx: GetTx,
+    inclusion_proof: MerkleProof,
+}
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<TxProof>(data);
+    let _ = serde_json::from_slice::<ShareProof>(data);
+    let _ = serde_json::from_slice::<RowProof>(data);
+    if let Ok(proof) = serde_json::from_slice::<MerkleProof>(data) {
+        let _ = BinaryMerkleProof::try_from(proof);
+    }
+    if let Ok(responses) = serde_json::from_slice::<Responses>(data) {
+        let _ = SharesProof::new(
+            responses.tx.height,
+            B256::ZERO,
+            U256::ZERO,
+            responses.inclusion_proof,
+            responses.tx.proof,
+        );
+    }
+});
@@ -0,0 +1,17 @@
+//! Converts raw RPC bytes into the sol types of the verifier.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use openvm_blobstream::verifier::{Namespace, NamespaceNode};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(namespace) = Namespace::try_from(data) {
+        assert_eq!(namespace.to_bytes().as_slice(), data);
+    }
+    if let Ok(node) = NamespaceNode::try_from(data) {
+        assert_eq!(node.to_bytes().as_slice(), data);
+    }
+    if let Some((version, id)) = data.split_first() {
+        let _ = Namespace::new(*version, id);
+    }
+});
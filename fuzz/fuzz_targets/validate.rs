@@ -0,0 +1,19 @@
+//! Runs the EVM and the native backends on arbitrary ABI-encoded inputs, they must never panic
+//! nor disagree.
+#![no_main]
+
+use alloy_sol_types::SolCall;
+use libfuzzer_sys::fuzz_target;
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = GuestInput::abi_decode_raw(data) else {
+        return;
+    };
+    if let Err(e) = guest::validate_with_backend(input, Backend::Both) {
+        assert!(!e.to_string().starts_with("backend mismatch"), "{e}");
+    }
+});
@@ -7,5 +7,41 @@ openvm::init!();
 
 fn main() {
     openvm_blobstream::install_revm_crypto(openvm_blobstream::openvm::Crypto);
-    openvm_blobstream::guest::validate(openvm::io::read::<GuestInput>()).unwrap();
+    let input = openvm::io::read::<GuestInput>();
+
+    #[cfg(feature = "profiling")]
+    {
+        let stop_before = openvm::io::read::<Option<openvm_blobstream::profile::Stage>>();
+        // the execution is expected to revert when stopped early
+        let _ = openvm_blobstream::guest::profile(input, stop_before);
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    {
+        use openvm_blobstream::guest::{Backend, Sp1Verifier};
+
+        #[cfg(feature = "test-verifier")]
+        let sp1 = Sp1Verifier::Mock {
+            vkey: openvm::io::read::<[u8; 32]>().into(),
+        };
+        #[cfg(not(feature = "test-verifier"))]
+        let sp1 = Sp1Verifier::Plonk;
+
+        #[cfg(feature = "namespace")]
+        {
+            let namespace =
+                openvm::io::read::<openvm_blobstream::native::namespace::NamespaceProof>();
+            let outputs = openvm_blobstream::guest::validate_namespace(
+                input,
+                &namespace,
+                Backend::DEFAULT,
+                sp1,
+            )
+            .unwrap();
+            openvm::io::reveal_bytes32(outputs.digest().0);
+        }
+
+        #[cfg(not(feature = "namespace"))]
+        openvm_blobstream::guest::validate_with(input, Backend::DEFAULT, sp1).unwrap();
+    }
 }
@@ -1,15 +1,18 @@
 use alloy::{
-    consensus::Transaction,
     hex,
-    primitives::{Address, B256, address},
-    providers::Provider,
-    sol_types::SolInterface,
+    primitives::{Address, B256},
 };
+#[cfg(feature = "devnet")]
+use openvm_blobstream::host::devnet::Devnet;
 use openvm_blobstream::{
     GuestInput,
-    da_oracle::{SP1Blobstream, SP1BlobstreamCalls, commitHeaderRangeCall},
-    host::{find_commit_tx, get_celestia_data_root_inclusion_proof, get_celestia_tx_by_hash},
-    verifier::SharesProof,
+    da_oracle::SP1Blobstream,
+    guest::Sp1Verifier,
+    host::{
+        artifact::ProofArtifacts, build_guest_input, check::cross_check, deployments,
+        retry::Failover, source::CometBftSource,
+    },
+    profile::{Stage, precompile_name},
 };
 use openvm_circuit::arch::instructions::exe::VmExe;
 use openvm_sdk::{
@@ -17,71 +20,40 @@ use openvm_sdk::{
     config::{AppConfig, SdkVmConfig},
     types::VersionedVmStarkProof,
 };
-use tendermint_rpc::Client;
 use tracing::info;
 
 pub const ELF: &[u8] = include_bytes!("../../target/openvm/release/blobstream-program.vmexe");
 pub const CELESTIA_RPC_URL: &str = "https://celestia-rpc.publicnode.com:443";
 pub const ETHEREUM_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
-pub const BLOBSTREAM_CONTRACT_ADDRESS: Address =
-    address!("0x7Cf3876F681Dbb6EdA8f6FfC45D66B996Df08fAe");
+pub const BLOBSTREAM_CONTRACT_ADDRESS: Address = deployments::ETHEREUM.address;
 const TX_HASH: &[u8] = &hex!("526684971CD73022587E79D66B45049C7F824D08E8AE53FA9DB43CA45B55B446");
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
     tracing_subscriber::fmt::init();
 
-    let ethereum_client =
-        alloy::providers::ProviderBuilder::new().connect_http(ETHEREUM_RPC_URL.parse()?);
-    let celestia_client = tendermint_rpc::HttpClient::new(CELESTIA_RPC_URL)?;
-    let blobstream_contract =
-        SP1Blobstream::new(BLOBSTREAM_CONTRACT_ADDRESS, ethereum_client.clone());
-
-    let tx = get_celestia_tx_by_hash(CELESTIA_RPC_URL, TX_HASH).await?;
-    let pfb_height = tx.height;
-    let celestia_block = celestia_client.block(pfb_height as u32).await?.block;
-    let celestia_block_data_hash =
-        B256::from_slice(celestia_block.header.data_hash.unwrap().as_ref());
-    info!(
-        "PayForBlobs tx at celestia height #{pfb_height} with data hash {celestia_block_data_hash}"
-    );
-
-    let (commit_tx_hash, event) =
-        find_commit_tx(&blobstream_contract, &ethereum_client, pfb_height).await?;
-    info!("found DataCommitmentStored event in ethereum tx {commit_tx_hash}: {event:?}");
+    #[cfg(not(feature = "devnet"))]
+    let guest_inputs = mainnet_guest_input().await?;
+    #[cfg(not(feature = "devnet"))]
+    openvm_blobstream::guest::validate(guest_inputs.clone())?; // run on host
 
-    let inclusion_proof = get_celestia_data_root_inclusion_proof(
-        CELESTIA_RPC_URL,
-        pfb_height,
-        event.startBlock,
-        event.endBlock,
-    )
-    .await?;
+    #[cfg(feature = "devnet")]
+    let (guest_inputs, devnet) = devnet_guest_input().await?;
+    #[cfg(feature = "devnet")]
+    openvm_blobstream::guest::validate_with(
+        guest_inputs.clone(),
+        openvm_blobstream::guest::Backend::DEFAULT,
+        devnet.sp1_verifier(),
+    )?; // run on host
+    info!("verified successfully on host");
 
-    let commit_tx = ethereum_client
-        .get_transaction_by_hash(commit_tx_hash)
-        .await?
-        .unwrap();
-
-    let SP1BlobstreamCalls::commitHeaderRange(commitHeaderRangeCall {
-        proof: commit_header_range_proof,
-        publicValues: public_values,
-    }) = SP1BlobstreamCalls::abi_decode(commit_tx.input().as_ref())?;
-
-    let guest_inputs = GuestInput {
-        commitHeaderRangeProof: commit_header_range_proof,
-        commitHeaderRangePublicValues: public_values,
-        sharesProof: SharesProof::new(
-            pfb_height,
-            celestia_block_data_hash,
-            event.proofNonce,
-            inclusion_proof,
-            tx.proof,
-        ),
-    };
+    #[cfg(feature = "light-client")]
+    verify_attested_header(&guest_inputs).await?;
 
-    openvm_blobstream::guest::validate(guest_inputs.clone())?; // run on host
-    info!("verified successfully on host");
+    if let Ok(url) = std::env::var("CELESTIA_CHECK_RPC_URL") {
+        cross_check(&guest_inputs, &CometBftSource { url: url.clone() }).await?;
+        info!("data hash confirmed by {url}");
+    }
 
     let app_config: AppConfig<SdkVmConfig> =
         toml::from_str(include_str!("../../program/openvm.toml"))?;
@@ -91,10 +63,18 @@ async fn main() -> eyre::Result<()> {
 
     let mut stdin = StdIn::default();
     stdin.write(&guest_inputs);
+    #[cfg(feature = "devnet")]
+    stdin.write(&devnet.vkey.0);
+    #[cfg(feature = "namespace")]
+    stdin.write(&namespace_proof(&guest_inputs).await?);
 
     let (_, (cost, instret)) = sdk.execute_metered_cost(app_exe.clone(), stdin.clone())?;
     info!("cells = {cost}, total_cycle = {instret}");
 
+    if let Ok(path) = std::env::var("PROFILING_VMEXE") {
+        profile_stages(&sdk, &path, &guest_inputs)?;
+    }
+
     let (proof, commit) = sdk.prove(app_exe.clone(), stdin.clone())?;
     let proof = VersionedVmStarkProof::new(proof)?;
 
@@ -102,3 +82,173 @@ async fn main() -> eyre::Result<()> {
     serde_json::to_writer_pretty(std::fs::File::create("blobstream.stark.proof")?, &proof)?;
     Ok(())
 }
+
+/// The comma separated endpoints of the env var `name`, or `default` when it is not set.
+fn rpc_urls(name: &str, default: &str) -> Vec<String> {
+    let urls = std::env::var(name).unwrap_or_default();
+    let urls: Vec<String> = urls
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(String::from)
+        .collect();
+    if urls.is_empty() {
+        vec![default.to_string()]
+    } else {
+        urls
+    }
+}
+
+/// Fetches the guest input of `TX_HASH` from mainnet, with retries, failing over the endpoints of
+/// `CELESTIA_RPC_URLS` and `ETHEREUM_RPC_URLS` in turn.
+#[cfg_attr(feature = "devnet", allow(dead_code))]
+async fn mainnet_guest_input() -> eyre::Result<GuestInput> {
+    let celestia = Failover::new(
+        rpc_urls("CELESTIA_RPC_URLS", CELESTIA_RPC_URL)
+            .into_iter()
+            .map(|url| CometBftSource { url })
+            .collect(),
+    );
+    let ethereum = Failover::new(
+        rpc_urls("ETHEREUM_RPC_URLS", ETHEREUM_RPC_URL)
+            .iter()
+            .map(|url| {
+                let client = alloy::providers::ProviderBuilder::new().connect_http(url.parse()?);
+                Ok(SP1Blobstream::new(BLOBSTREAM_CONTRACT_ADDRESS, client))
+            })
+            .collect::<eyre::Result<_>>()?,
+    );
+
+    let tx_hash = B256::from_slice(TX_HASH);
+    let guest_inputs = match std::env::var("SP1_PROOF_ARTIFACT") {
+        Ok(path) => {
+            let artifacts = ProofArtifacts::load([path], Sp1Verifier::default())?;
+            build_guest_input(&celestia, &artifacts, tx_hash).await?
+        }
+        Err(_) => build_guest_input(&celestia, &ethereum, tx_hash).await?,
+    };
+    let tuple = &guest_inputs.sharesProof.attestationProof.tuple;
+    info!(
+        "PayForBlobs tx at celestia height #{} with data hash {}",
+        tuple.height, tuple.dataRoot
+    );
+    Ok(guest_inputs)
+}
+
+/// Builds the guest input of the devnet tx `DEVNET_TX_HASH`, with a data commitment computed from
+/// the devnet at `DEVNET_CELESTIA_RPC_URL`. The program must be built with `test-verifier`.
+#[cfg(feature = "devnet")]
+async fn devnet_guest_input() -> eyre::Result<(GuestInput, Devnet)> {
+    let var = |name: &str| std::env::var(name).map_err(|_| eyre::eyre!("{name} is not set"));
+    let devnet = Devnet {
+        celestia_rpc_url: var("DEVNET_CELESTIA_RPC_URL")?,
+        range_len: match var("DEVNET_RANGE_LEN") {
+            Ok(len) => len.parse()?,
+            Err(_) => 100,
+        },
+        vkey: match var("DEVNET_PROGRAM_VKEY") {
+            Ok(vkey) => vkey.parse()?,
+            Err(_) => openvm_blobstream::verifier::SP1_BLOB_STREAM_PROGRAM_VKEY,
+        },
+    };
+    let tx_hash = hex::decode(var("DEVNET_TX_HASH")?)?;
+    let (event, guest_inputs) = devnet.build_guest_input(&tx_hash).await?;
+    info!("computed devnet data commitment: {event:?}");
+    Ok((guest_inputs, devnet))
+}
+
+/// Verifies the attested Celestia header from the trusted header `CELESTIA_TRUSTED_HEIGHT` with
+/// hash `CELESTIA_TRUSTED_HASH`.
+#[cfg(feature = "light-client")]
+async fn verify_attested_header(guest_inputs: &GuestInput) -> eyre::Result<()> {
+    use openvm_blobstream::host::light_client::{
+        LightClientOptions, TrustRoot, check_attested_header, verify_header,
+    };
+
+    let var = |name: &str| std::env::var(name).map_err(|_| eyre::eyre!("{name} is not set"));
+    let trust_root = TrustRoot {
+        height: var("CELESTIA_TRUSTED_HEIGHT")?.parse()?,
+        hash: var("CELESTIA_TRUSTED_HASH")?.parse()?,
+    };
+    let height = guest_inputs.sharesProof.attestationProof.tuple.height.to();
+    let header = verify_header(
+        &rpc_urls("CELESTIA_RPC_URLS", CELESTIA_RPC_URL)[0],
+        trust_root,
+        height,
+        &LightClientOptions::default(),
+    )
+    .await?;
+    check_attested_header(guest_inputs, &header)?;
+    info!(
+        "celestia header {height} verified from the trusted header: {}",
+        header.hash
+    );
+    Ok(())
+}
+
+/// Fetches the proof of all the shares of namespace `CELESTIA_NAMESPACE` (29 bytes, hex) in the
+/// attested block from the celestia-node at `CELESTIA_NODE_URL`, and checks it on the host.
+#[cfg(feature = "namespace")]
+async fn namespace_proof(
+    guest_inputs: &GuestInput,
+) -> eyre::Result<openvm_blobstream::native::namespace::NamespaceProof> {
+    use openvm_blobstream::{
+        host::celestia_node::CelestiaNodeClient, native::namespace, verifier::Namespace,
+    };
+
+    let var = |name: &str| std::env::var(name).map_err(|_| eyre::eyre!("{name} is not set"));
+    let namespace = Namespace::try_from(hex::decode(var("CELESTIA_NAMESPACE")?)?.as_slice())?;
+    let node = CelestiaNodeClient::new(
+        var("CELESTIA_NODE_URL")?,
+        std::env::var("CELESTIA_NODE_AUTH_TOKEN").ok(),
+    );
+    let tuple = &guest_inputs.sharesProof.attestationProof.tuple;
+    let height = tuple.height.to();
+    let (_, proof) = node.get_namespace_proof(height, &namespace).await?;
+    eyre::ensure!(
+        namespace::verify(&proof, &tuple.dataRoot),
+        "incomplete proof of namespace {} at celestia height {height}",
+        hex::encode(namespace.to_bytes())
+    );
+    info!(
+        "{} shares of namespace {} at celestia height {height}",
+        proof.shares().count(),
+        hex::encode(namespace.to_bytes())
+    );
+    Ok(proof)
+}
+
+/// Reports the cost of each verification stage, using a guest built with the `profiling` feature
+/// and the backend feature of this script.
+///
+/// The guest reverts once the stage it is told to stop before is reached, so the cost of a stage
+/// is the difference between the runs stopped before it and before the next one.
+fn profile_stages(sdk: &Sdk, exe_path: &str, guest_inputs: &GuestInput) -> eyre::Result<()> {
+    let profiling_exe: VmExe<F> = bitcode::deserialize(&std::fs::read(exe_path)?)?;
+    let (_, report) = openvm_blobstream::guest::profile(guest_inputs.clone(), None)?;
+
+    let mut previous = (0, 0);
+    for stage in Stage::ALL {
+        let mut stdin = StdIn::default();
+        stdin.write(guest_inputs);
+        stdin.write(&stage.next());
+
+        let (_, (cost, instret)) = sdk.execute_metered_cost(profiling_exe.clone(), stdin)?;
+        info!(
+            "{:>16}: cells = {}, cycles = {}",
+            stage.name(),
+            cost - previous.0,
+            instret - previous.1
+        );
+        for ((_, address), count) in report
+            .precompile_calls
+            .iter()
+            .filter(|((precompile_stage, _), _)| *precompile_stage == stage)
+        {
+            let name = precompile_name(*address).map_or_else(|| address.to_string(), String::from);
+            info!("{:>16}  {name} x {count}", "");
+        }
+        previous = (cost, instret);
+    }
+    Ok(())
+}
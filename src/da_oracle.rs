@@ -7,6 +7,8 @@ sol! {
     contract SP1Blobstream {
         function commitHeaderRange(bytes calldata proof, bytes calldata publicValues) external {}
 
+        function state_dataCommitments(uint256 nonce) external view returns (bytes32);
+
         event DataCommitmentStored(
             uint256 proofNonce,
             uint64 indexed startBlock,
@@ -16,6 +18,54 @@ sol! {
     }
 }
 
+// Blobstream X, the implementation of the Blobstream proxies before SP1 Blobstream. Headers are
+// proven with plonky2 through the Succinct gateway, which calls `commitHeaderRange` back.
+#[cfg(feature = "host")]
+sol! {
+    #[sol(rpc)]
+    #[derive(Debug)]
+    contract BlobstreamX {
+        function commitHeaderRange(uint64 _targetBlock) external {}
+
+        event DataCommitmentStored(
+            uint256 proofNonce,
+            uint64 indexed startBlock,
+            uint64 indexed endBlock,
+            bytes32 indexed dataCommitment
+        );
+    }
+}
+
+// The Quantum Gravity Bridge, the first Blobstream, attested by the signatures of the Celestia
+// validators. Its events carry no block range.
+#[cfg(feature = "host")]
+sol! {
+    #[sol(rpc)]
+    #[derive(Debug)]
+    contract QuantumGravityBridge {
+        struct Validator {
+            address addr;
+            uint256 power;
+        }
+
+        struct Signature {
+            uint8 v;
+            bytes32 r;
+            bytes32 s;
+        }
+
+        function submitDataRootTupleRoot(
+            uint256 _newNonce,
+            uint256 _validatorSetNonce,
+            bytes32 _dataRootTupleRoot,
+            Validator[] calldata _currentValidatorSet,
+            Signature[] calldata _sigs
+        ) external {}
+
+        event DataRootTupleRootEvent(uint256 indexed nonce, bytes32 dataRootTupleRoot);
+    }
+}
+
 #[cfg(not(feature = "host"))]
 sol! {
     #[derive(Debug)]
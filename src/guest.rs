@@ -1,50 +1,485 @@
-use crate::{verifier, verifier::verifyCall};
-use alloy_primitives::{Address, Bytes, U256, hex, keccak256};
-use alloy_sol_types::{SolCall, SolValue};
-use eyre::eyre;
+use crate::{
+    native::{
+        self,
+        namespace::{NamespaceOutputs, NamespaceProof},
+    },
+    profile::{Stage, StageInspector, StageReport},
+    verifier,
+    verifier::{SP1_BLOB_STREAM_PROGRAM_VKCall, VERIFIER_HASHCall, VerifierErrors, verifyCall},
+};
+use alloy_primitives::{Address, B256, Bytes, U256, hex, keccak256};
+use alloy_sol_types::{SolCall, SolInterface, SolValue};
+use eyre::{bail, ensure, eyre};
+#[cfg(any(test, feature = "test-utils"))]
+use revm::InspectCommitEvm;
 use revm::{
-    Context, ExecuteCommitEvm, MainBuilder, MainContext,
+    Context, ExecuteCommitEvm, ExecuteEvm, InspectEvm, MainBuilder, MainContext,
     context::{
         TxEnv,
         result::{ExecutionResult, Output},
     },
-    database::InMemoryDB,
+    database::CacheDB,
+    database_interface::{Database, DatabaseRef},
+    handler::MainnetContext,
+    primitives::hardfork::SpecId,
     state::{AccountInfo, Bytecode},
 };
+use std::convert::Infallible;
+#[cfg(any(test, feature = "test-utils"))]
+use stub::{Sp1Stub, StubbedStages};
+
+#[cfg(any(test, feature = "test-utils"))]
+mod stub;
+
+pub(crate) const VERIFIER_ADDRESS: Address = Address::repeat_byte(0x42);
+const CALLER_ADDRESS: Address = Address::repeat_byte(0xcc);
+/// The spec of both execution modes.
+const SPEC: SpecId = SpecId::PRAGUE;
+
+/// How the verifier bytecode is executed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// A mainnet context over an in-memory database with a funded caller, the resulting state is
+    /// committed to the database.
+    Mainnet,
+    /// A context serving only the verifier code, without gas limit, fees, nonce check nor state
+    /// commit.
+    ///
+    /// The verifier never writes state that outlives the call, so the result is the same as
+    /// [`ExecutionMode::Mainnet`]. Opcodes are still charged gas, revm has no switch for it and the
+    /// outcome of the bytecode depends on it: the `Verify` call of the verifier to itself forwards
+    /// 63/64 of the remaining gas. The mainnet handler only adds per-transaction checks on top.
+    #[default]
+    Lean,
+}
+
+/// Which implementation of the verifier checks the input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// The verifier bytecode, the audited reference.
+    Evm,
+    /// The Rust port of the verifier in [`native`].
+    Native,
+    /// Both, failing unless they both accept the input or reject it for the same reason.
+    Both,
+}
+
+impl Backend {
+    /// The backend selected by the `backend-native` and `backend-both` features, the EVM if none
+    /// is enabled.
+    pub const DEFAULT: Self = if cfg!(feature = "backend-both") {
+        Backend::Both
+    } else if cfg!(feature = "backend-native") {
+        Backend::Native
+    } else {
+        Backend::Evm
+    };
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The verifier of the SP1 proofs, which the verifier bytecode calls with its `Verify` function.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Sp1Verifier {
+    /// The SP1 Plonk verifier of the bytecode.
+    #[default]
+    Plonk,
+    /// Accepts any proof, for tests only.
+    ///
+    /// Only the pairing check is skipped, the verifier selector of the proof and the public values
+    /// are checked as usual.
+    #[cfg(any(test, feature = "test-utils"))]
+    Stub,
+    /// A mock SP1 verifier configured with the SP1 Blobstream program key `vkey` instead of the one
+    /// of the bytecode, for tests and devnets only.
+    ///
+    /// Accepts the proofs of [`synthetic::sp1_mock_proof`](crate::synthetic::sp1_mock_proof),
+    /// which commit to `vkey` and to the digest of the public values.
+    #[cfg(feature = "test-verifier")]
+    Mock { vkey: B256 },
+}
 
 pub fn validate(input: verifyCall) -> eyre::Result<()> {
-    const VERIFIER_ADDRESS: Address = Address::repeat_byte(0x42);
-    const CALLER_ADDRESS: Address = Address::repeat_byte(0xcc);
+    validate_with_backend(input, Backend::DEFAULT)
+}
 
-    let mut db = InMemoryDB::default();
+pub fn validate_with_backend(input: verifyCall, backend: Backend) -> eyre::Result<()> {
+    validate_with(input, backend, Sp1Verifier::default())
+}
+
+/// Same as [`validate_with_backend`], with the SP1 proofs checked by `sp1`.
+pub fn validate_with(input: verifyCall, backend: Backend, sp1: Sp1Verifier) -> eyre::Result<()> {
+    let expected_commit = keccak256(&input.abi_encode()[4..]);
+    let commit = match backend {
+        Backend::Evm => execute(input, sp1)?,
+        Backend::Native => native::verify_with(&input, sp1)?,
+        Backend::Both => {
+            let native = native::verify_with(&input, sp1);
+            let evm = execute(input, sp1);
+            match (evm, native) {
+                (Ok(evm), Ok(native)) => {
+                    ensure!(
+                        evm == native,
+                        "backend mismatch: evm committed {evm}, native committed {native}"
+                    );
+                    evm
+                }
+                (Err(evm), Err(native)) if revert_reason(&evm) == revert_reason(&native) => {
+                    return Err(evm);
+                }
+                (evm, native) => {
+                    return Err(eyre!(
+                        "backend mismatch: evm returned {evm:?}, native returned {native:?}"
+                    ));
+                }
+            }
+        }
+    };
+    check_commit(commit, expected_commit)
+}
 
-    db.insert_account_info(
-        VERIFIER_ADDRESS,
-        AccountInfo::from_bytecode(Bytecode::new_raw(verifier::DEPLOYED_BYTECODE.clone())),
+/// Same as [`validate_with`], also checking that `namespace` holds all the shares of its namespace
+/// in the block attested by `input`. Returns what the two proofs attest together.
+pub fn validate_namespace(
+    input: verifyCall,
+    namespace: &NamespaceProof,
+    backend: Backend,
+    sp1: Sp1Verifier,
+) -> eyre::Result<NamespaceOutputs> {
+    let data_root = input.sharesProof.attestationProof.tuple.dataRoot;
+    let input_commit = keccak256(&input.abi_encode()[4..]);
+    validate_with(input, backend, sp1)?;
+    ensure!(
+        native::namespace::verify(namespace, &data_root),
+        "incomplete namespace proof"
     );
-    db.insert_account_info(CALLER_ADDRESS, AccountInfo::from_balance(U256::MAX));
+    Ok(NamespaceOutputs {
+        inputCommit: input_commit,
+        namespace: namespace.namespace.to_bytes().into(),
+        dataRoot: data_root,
+        sharesDigest: namespace.shares_digest(),
+    })
+}
+
+pub fn validate_with_mode(input: verifyCall, mode: ExecutionMode) -> eyre::Result<()> {
+    validate_in_mode(input, mode, Sp1Verifier::default())
+}
+
+/// Same as [`validate_with_mode`], with the SP1 proofs checked by `sp1`.
+pub fn validate_in_mode(
+    input: verifyCall,
+    mode: ExecutionMode,
+    sp1: Sp1Verifier,
+) -> eyre::Result<()> {
+    let expected_commit = keccak256(&input.abi_encode()[4..]);
+    let commit = match mode {
+        ExecutionMode::Mainnet => execute_mainnet(input, sp1)?,
+        ExecutionMode::Lean => execute(input, sp1)?,
+    };
+    check_commit(commit, expected_commit)
+}
+
+/// Runs the verifier bytecode on `input` in the mainnet mode, returns the commit it outputs.
+fn execute_mainnet(input: verifyCall, sp1: Sp1Verifier) -> eyre::Result<B256> {
+    let tx = build_tx(input.abi_encode())?;
+    let context = Context::mainnet()
+        .with_db(build_db())
+        .modify_cfg_chained(|cfg| cfg.spec = SPEC);
+    let result = match sp1 {
+        Sp1Verifier::Plonk => context.build_mainnet().transact_commit(tx)?,
+        #[cfg(any(test, feature = "test-utils"))]
+        _ => context
+            .build_mainnet_with_inspector(Sp1Stub::new(sp1)?)
+            .inspect_tx_commit(tx)?,
+    };
+    check_result(result).and_then(decode_commit)
+}
+
+/// Runs the verifier bytecode on `input` in the lean mode, returns the commit it outputs.
+fn execute(input: verifyCall, sp1: Sp1Verifier) -> eyre::Result<B256> {
+    call_verifier(input.abi_encode(), sp1).and_then(decode_commit)
+}
+
+/// Calls the verifier with `data` in the lean mode, returns the output of the call.
+pub(crate) fn call_verifier(data: Vec<u8>, sp1: Sp1Verifier) -> eyre::Result<Bytes> {
+    let tx = lean_tx(build_tx(data)?);
+    let context = lean_context(VerifierDb::new());
+    let result = match sp1 {
+        Sp1Verifier::Plonk => context.build_mainnet().transact_one(tx)?,
+        #[cfg(any(test, feature = "test-utils"))]
+        _ => context
+            .build_mainnet_with_inspector(Sp1Stub::new(sp1)?)
+            .inspect_one_tx(tx)?,
+    };
+    check_result(result)
+}
 
-    let mut evm = Context::mainnet().with_db(db).build_mainnet();
+/// Same as [`call_verifier`], with the execution inspected by `stages`.
+pub(crate) fn inspect_verifier(
+    data: Vec<u8>,
+    sp1: Sp1Verifier,
+    stages: &mut StageInspector,
+) -> eyre::Result<Bytes> {
+    let tx = lean_tx(build_tx(data)?);
+    let context = lean_context(VerifierDb::new());
+    let result = match sp1 {
+        Sp1Verifier::Plonk => context
+            .build_mainnet_with_inspector(stages)
+            .inspect_one_tx(tx)?,
+        #[cfg(any(test, feature = "test-utils"))]
+        _ => context
+            .build_mainnet_with_inspector(StubbedStages::new(sp1, stages)?)
+            .inspect_one_tx(tx)?,
+    };
+    check_result(result)
+}
+
+/// The SP1 Blobstream program key the verifier checks the SP1 proofs against.
+pub fn program_vkey(sp1: Sp1Verifier) -> eyre::Result<B256> {
+    #[cfg(feature = "test-verifier")]
+    if let Sp1Verifier::Mock { vkey } = sp1 {
+        return Ok(vkey);
+    }
+    let output = call_verifier(SP1_BLOB_STREAM_PROGRAM_VKCall {}.abi_encode(), sp1)?;
+    Ok(SP1_BLOB_STREAM_PROGRAM_VKCall::abi_decode_returns(&output)?)
+}
+
+/// The hash of the SP1 verifier, whose first 4 bytes prefix the SP1 proofs it accepts.
+pub fn verifier_hash(sp1: Sp1Verifier) -> eyre::Result<B256> {
+    let output = call_verifier(VERIFIER_HASHCall {}.abi_encode(), sp1)?;
+    Ok(VERIFIER_HASHCall::abi_decode_returns(&output)?)
+}
 
-    let input = input.abi_encode();
-    let expected_commit = keccak256(&input[4..]);
-    let tx = TxEnv::builder()
+/// Same as [`validate`], with the stages of the [`Backend::DEFAULT`] backend tracked by a
+/// [`StageInspector`].
+///
+/// If `stop_before` is set, the verification fails once that stage is reached.
+pub fn profile(
+    input: verifyCall,
+    stop_before: Option<Stage>,
+) -> eyre::Result<(eyre::Result<()>, StageReport)> {
+    profile_with(input, Backend::DEFAULT, Sp1Verifier::default(), stop_before)
+}
+
+/// Same as [`profile`] with `backend`, and the SP1 proofs checked by `sp1`.
+///
+/// With [`Backend::Both`], both backends run up to `stop_before` and the report counts the
+/// precompile calls of both.
+pub fn profile_with(
+    input: verifyCall,
+    backend: Backend,
+    sp1: Sp1Verifier,
+    stop_before: Option<Stage>,
+) -> eyre::Result<(eyre::Result<()>, StageReport)> {
+    let expected_commit = keccak256(&input.abi_encode()[4..]);
+    let mut stages = StageInspector::new(stop_before);
+    let commit = match backend {
+        Backend::Evm => inspect_execute(&input, sp1, &mut stages),
+        Backend::Native => native::verify_staged(&input, sp1, &mut stages),
+        Backend::Both => {
+            let native = native::verify_staged(&input, sp1, &mut stages);
+            let evm = inspect_execute(&input, sp1, &mut stages);
+            native.and_then(|native| {
+                let evm = evm?;
+                ensure!(
+                    evm == native,
+                    "backend mismatch: evm committed {evm}, native committed {native}"
+                );
+                Ok(evm)
+            })
+        }
+    };
+    let result = commit.and_then(|commit| check_commit(commit, expected_commit));
+    Ok((result, stages.into_report()))
+}
+
+/// Same as [`execute`], with the stages of the execution tracked by `stages`.
+fn inspect_execute(
+    input: &verifyCall,
+    sp1: Sp1Verifier,
+    stages: &mut StageInspector,
+) -> eyre::Result<B256> {
+    let result = inspect_verifier(input.abi_encode(), sp1, stages).and_then(decode_commit);
+    match (result, stages.stopped()) {
+        (Err(_), Some(stage)) => bail!("stopped before stage {}", stage.name()),
+        (result, _) => result,
+    }
+}
+
+/// The verifier and a funded caller.
+fn build_db() -> CacheDB<VerifierDb> {
+    let mut db = CacheDB::new(VerifierDb::new());
+    db.insert_account_info(CALLER_ADDRESS, AccountInfo::from_balance(U256::MAX));
+    db
+}
+
+fn build_tx(data: Vec<u8>) -> eyre::Result<TxEnv> {
+    TxEnv::builder()
         .caller(CALLER_ADDRESS)
         .to(VERIFIER_ADDRESS)
-        .data(Bytes::from(input))
+        .data(Bytes::from(data))
         .build()
-        .unwrap();
-    let result = evm.transact_commit(tx)?;
+        .map_err(|e| eyre!("invalid tx: {e:?}"))
+}
 
+/// A mainnet context whose block accepts any gas limit and any nonce, over a [`VerifierDb`].
+fn lean_context(db: VerifierDb) -> MainnetContext<VerifierDb> {
+    Context::mainnet()
+        .with_db(db)
+        .modify_cfg_chained(|cfg| {
+            cfg.spec = SPEC;
+            cfg.disable_nonce_check = true;
+        })
+        .modify_block_chained(|block| {
+            block.gas_limit = u64::MAX;
+            block.basefee = 0;
+        })
+}
+
+/// Lifts the gas limit of `tx`, it is free since the gas price is zero.
+fn lean_tx(mut tx: TxEnv) -> TxEnv {
+    tx.gas_limit = u64::MAX;
+    tx.gas_price = 0;
+    tx
+}
+
+/// A database holding only the verifier, every other account is empty.
+#[derive(Debug, Clone)]
+struct VerifierDb {
+    verifier: AccountInfo,
+}
+
+impl VerifierDb {
+    fn new() -> Self {
+        Self {
+            verifier: AccountInfo::from_bytecode(Bytecode::new_raw(
+                verifier::DEPLOYED_BYTECODE.clone(),
+            )),
+        }
+    }
+}
+
+impl DatabaseRef for VerifierDb {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok((address == VERIFIER_ADDRESS).then(|| self.verifier.clone()))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // the code is always served along with the account info
+        Ok(self
+            .verifier
+            .code
+            .clone()
+            .filter(|_| code_hash == self.verifier.code_hash)
+            .unwrap_or_default())
+    }
+
+    fn storage_ref(&self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+        Ok(U256::ZERO)
+    }
+
+    fn block_hash_ref(&self, _number: u64) -> Result<B256, Self::Error> {
+        Ok(B256::ZERO)
+    }
+}
+
+impl Database for VerifierDb {
+    type Error = Infallible;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+fn decode_commit(output: Bytes) -> eyre::Result<B256> {
+    B256::try_from(output.as_ref()).map_err(|_| eyre!("invalid commit: {output}"))
+}
+
+fn check_commit(commit: B256, expected_commit: B256) -> eyre::Result<()> {
+    if commit != expected_commit {
+        return Err(eyre!(
+            "commit mismatch: got {commit}, expected {expected_commit}"
+        ));
+    }
+    Ok(())
+}
+
+fn check_result(result: ExecutionResult) -> eyre::Result<Bytes> {
     match result {
         ExecutionResult::Success {
             output: Output::Call(output),
             ..
-        } if expected_commit.as_slice() == output.as_ref() => Ok(()),
-        ExecutionResult::Revert { output, .. } if output.starts_with(&hex!("08c379a0")) => {
-            let reason = String::abi_decode(&output[4..])?;
-            Err(eyre!("EVM reverted: {reason}"))
-        }
+        } => Ok(output),
+        ExecutionResult::Revert { output, .. } => Err(Reverted::from_output(&output).into()),
         other => Err(eyre!("execution failed: {other:?}")),
     }
 }
+
+/// A revert of the verifier bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reverted {
+    /// The message of an `Error(string)`, the custom error of the verifier, the panic code or else
+    /// the revert data.
+    pub reason: String,
+}
+
+impl Reverted {
+    /// The revert with data `output`.
+    pub fn from_output(output: &[u8]) -> Self {
+        let reason = if let Some(data) = output.strip_prefix(&hex!("08c379a0"))
+            && let Ok(reason) = String::abi_decode(data)
+        {
+            reason
+        } else if let Some(data) = output.strip_prefix(&hex!("4e487b71"))
+            && let Ok(code) = U256::abi_decode(data)
+        {
+            format!("panic {code:#x}")
+        } else if let Ok(error) = VerifierErrors::abi_decode(output) {
+            // e.g. a proof for another SP1 verifier version
+            format!("{error:?}")
+        } else {
+            Bytes::copy_from_slice(output).to_string()
+        };
+        Self { reason }
+    }
+
+    /// The revert of a failed arithmetic check, e.g. an underflow.
+    pub fn arithmetic() -> Self {
+        Self::from_output(&[&hex!("4e487b71")[..], &U256::from(0x11).abi_encode()].concat())
+    }
+}
+
+impl std::fmt::Display for Reverted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EVM reverted: {}", self.reason)
+    }
+}
+
+impl std::error::Error for Reverted {}
+
+/// The reason `error` rejects the input for, the reason of the [`Reverted`] it wraps if any, as the
+/// native backend wraps the reverts of the verifier calls it makes.
+fn revert_reason(error: &eyre::Report) -> String {
+    match error.downcast_ref::<Reverted>() {
+        Some(reverted) => reverted.reason.clone(),
+        None => error.to_string(),
+    }
+}
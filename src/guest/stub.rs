@@ -0,0 +1,100 @@
+//! The SP1 verifiers of [`Sp1Verifier::Stub`] and `Sp1Verifier::Mock`.
+use super::{Sp1Verifier, VERIFIER_ADDRESS};
+use crate::{profile::StageInspector, verifier::VerifyCall};
+#[cfg(feature = "test-verifier")]
+use alloy_primitives::B256;
+use alloy_sol_types::{SolCall, SolValue};
+use eyre::bail;
+use revm::{
+    Inspector,
+    context::ContextTr,
+    interpreter::{
+        CallInputs, CallOutcome, Gas, InstructionResult, Interpreter, InterpreterResult,
+    },
+};
+
+/// Answers the `Verify` calls of the verifier to itself in place of its Plonk verifier.
+#[derive(Debug)]
+pub(super) enum Sp1Stub {
+    /// [`Sp1Verifier::Stub`].
+    Any,
+    /// [`Sp1Verifier::Mock`].
+    #[cfg(feature = "test-verifier")]
+    Mock { vkey: B256 },
+}
+
+impl Sp1Stub {
+    /// The stub standing for `sp1`, the Plonk verifier of the bytecode has none.
+    pub(super) fn new(sp1: Sp1Verifier) -> eyre::Result<Self> {
+        match sp1 {
+            Sp1Verifier::Plonk => bail!("the Plonk verifier is not stubbed"),
+            Sp1Verifier::Stub => Ok(Self::Any),
+            #[cfg(feature = "test-verifier")]
+            Sp1Verifier::Mock { vkey } => Ok(Self::Mock { vkey }),
+        }
+    }
+
+    /// Whether the SP1 verifier accepts the `Verify` call with calldata `input`.
+    fn verify(&self, input: &[u8]) -> bool {
+        match (self, input) {
+            (Self::Any, _) => true,
+            // the program key of the bytecode is replaced by the one of the mock
+            #[cfg(feature = "test-verifier")]
+            (Self::Mock { vkey }, input) => VerifyCall::abi_decode(input).is_ok_and(|call| {
+                call.public_inputs.len() == 2
+                    && call.proof == (*vkey, call.public_inputs[1]).abi_encode()
+            }),
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for Sp1Stub {
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if inputs.target_address != VERIFIER_ADDRESS {
+            return None;
+        }
+        let input = inputs.input.bytes(context);
+        if !input.starts_with(&VerifyCall::SELECTOR) {
+            return None;
+        }
+        Some(CallOutcome::new(
+            InterpreterResult::new(
+                InstructionResult::Return,
+                self.verify(&input).abi_encode().into(),
+                Gas::new(inputs.gas_limit),
+            ),
+            inputs.return_memory_offset.clone(),
+        ))
+    }
+}
+
+/// A [`StageInspector`] over an execution with the SP1 verifier stubbed.
+#[derive(Debug)]
+pub(super) struct StubbedStages<'a> {
+    stub: Sp1Stub,
+    stages: &'a mut StageInspector,
+}
+
+impl<'a> StubbedStages<'a> {
+    pub(super) fn new(sp1: Sp1Verifier, stages: &'a mut StageInspector) -> eyre::Result<Self> {
+        Ok(Self {
+            stub: Sp1Stub::new(sp1)?,
+            stages,
+        })
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for StubbedStages<'_> {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        self.stages.step(interp, context);
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let outcome = self.stages.call(context, inputs);
+        outcome.or_else(|| self.stub.call(context, inputs))
+    }
+
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.stages.call_end(context, inputs, outcome);
+    }
+}
@@ -1,15 +1,44 @@
 use crate::{
+    GuestInput,
     da_oracle::{DataCommitmentStored, SP1BlobstreamInstance},
+    host::{
+        deployments::ContractVersion,
+        finality::Finality,
+        source::{CelestiaSource, EthereumSource},
+    },
     verifier::{
         AttestationProof, BinaryMerkleProof, DataRootTuple, Namespace, NamespaceMerkleMultiproof,
         NamespaceNode, SharesProof,
     },
 };
-use alloy_primitives::{B256, Bytes, U256, hex};
+use alloy_primitives::{Address, B256, Bytes, U256, hex};
 use alloy_provider::Provider;
+use alloy_rpc_types_eth::Filter;
+use alloy_sol_types::SolEvent;
+use eyre::{OptionExt, ensure, eyre};
 use serde::Deserialize;
+use std::{sync::LazyLock, time::Duration};
 use tendermint::serializers;
 
+pub mod artifact;
+pub mod cache;
+pub mod celestia_node;
+pub mod check;
+pub mod commit_call;
+pub mod deployments;
+#[cfg(feature = "devnet")]
+pub mod devnet;
+pub mod finality;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod indexer;
+#[cfg(feature = "light-client")]
+pub mod light_client;
+#[cfg(feature = "mock-rpc")]
+pub mod mock;
+pub mod retry;
+pub mod source;
+
 #[derive(Debug, Deserialize)]
 pub struct GetTx {
     #[serde(with = "serializers::from_str")]
@@ -17,6 +46,14 @@ pub struct GetTx {
     pub proof: TxProof,
 }
 
+/// The header fields of a Celestia block the host relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub height: u64,
+    pub hash: B256,
+    pub data_hash: B256,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct MerkleProof {
     #[serde(with = "serializers::from_str")]
@@ -45,8 +82,8 @@ impl SharesProof {
         inclusion_proof_nonce: U256,
         inclusion_proof: MerkleProof,
         tx_proof: TxProof,
-    ) -> Self {
-        SharesProof {
+    ) -> eyre::Result<Self> {
+        Ok(SharesProof {
             data: tx_proof
                 .data
                 .into_iter()
@@ -56,32 +93,34 @@ impl SharesProof {
             shareProofs: tx_proof
                 .share_proofs
                 .into_iter()
-                .map(|proof| NamespaceMerkleMultiproof {
-                    beginKey: U256::from(proof.start),
-                    endKey: U256::from(proof.end),
-                    sideNodes: proof
-                        .nodes
-                        .into_iter()
-                        .map(|node| NamespaceNode::from(node.as_ref()))
-                        .collect(),
+                .map(|proof| {
+                    Ok(NamespaceMerkleMultiproof {
+                        beginKey: U256::from(proof.start),
+                        endKey: U256::from(proof.end),
+                        sideNodes: proof
+                            .nodes
+                            .iter()
+                            .map(|node| NamespaceNode::try_from(node.as_ref()))
+                            .collect::<eyre::Result<_>>()?,
+                    })
                 })
-                .collect(),
+                .collect::<eyre::Result<_>>()?,
 
-            namespace: Namespace::new(tx_proof.namespace_version, tx_proof.namespace_id.as_ref()),
+            namespace: Namespace::new(tx_proof.namespace_version, tx_proof.namespace_id.as_ref())?,
 
             rowRoots: tx_proof
                 .row_proof
                 .row_roots
                 .iter()
-                .map(|root| NamespaceNode::from(root.as_ref()))
-                .collect(),
+                .map(|root| NamespaceNode::try_from(root.as_ref()))
+                .collect::<eyre::Result<_>>()?,
 
             rowProofs: tx_proof
                 .row_proof
                 .proofs
                 .into_iter()
-                .map(BinaryMerkleProof::from)
-                .collect(),
+                .map(BinaryMerkleProof::try_from)
+                .collect::<eyre::Result<_>>()?,
 
             attestationProof: AttestationProof {
                 tupleRootNonce: inclusion_proof_nonce,
@@ -91,23 +130,46 @@ impl SharesProof {
                     dataRoot: celestia_block_data_hash,
                 },
 
-                proof: inclusion_proof.into(),
+                proof: inclusion_proof.try_into()?,
             },
-        }
+        })
     }
 }
 
-impl From<MerkleProof> for BinaryMerkleProof {
-    fn from(proof: MerkleProof) -> Self {
-        Self {
+impl TryFrom<MerkleProof> for BinaryMerkleProof {
+    type Error = eyre::Report;
+
+    fn try_from(proof: MerkleProof) -> eyre::Result<Self> {
+        Ok(Self {
             sideNodes: proof
                 .aunts
                 .iter()
-                .map(|node| B256::from_slice(node.as_ref()))
-                .collect(),
+                .map(|node| {
+                    B256::try_from(node.as_slice())
+                        .map_err(|_| eyre!("invalid merkle proof node length: {}", node.len()))
+                })
+                .collect::<eyre::Result<_>>()?,
             key: U256::from(proof.index),
             numLeaves: U256::from(proof.total),
-        }
+        })
+    }
+}
+
+impl TryFrom<BinaryMerkleProof> for MerkleProof {
+    type Error = eyre::Report;
+
+    fn try_from(proof: BinaryMerkleProof) -> eyre::Result<Self> {
+        Ok(Self {
+            total: proof
+                .numLeaves
+                .try_into()
+                .map_err(|_| eyre!("merkle proof leaf count too large: {}", proof.numLeaves))?,
+            index: proof
+                .key
+                .try_into()
+                .map_err(|_| eyre!("merkle proof key too large: {}", proof.key))?,
+            aunts: proof.sideNodes.iter().map(|node| node.to_vec()).collect(),
+        })
     }
 }
 
@@ -121,6 +183,66 @@ struct GetDataRootInclusionProof {
     proof: MerkleProof,
 }
 
+#[derive(Debug, Deserialize)]
+struct GetBlock {
+    block: Block,
+}
+
+#[derive(Debug, Deserialize)]
+struct Block {
+    header: Header,
+}
+
+#[derive(Debug, Deserialize)]
+struct Header {
+    #[serde(with = "serializers::bytes::hexstring")]
+    data_hash: Vec<u8>,
+}
+
+/// A `/block` response, with the hash and the height of the block.
+#[derive(Debug, Deserialize)]
+struct GetBlockHeader {
+    block_id: BlockId,
+    block: HeaderBlock,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeaderBlock {
+    header: HeightHeader,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeightHeader {
+    #[serde(with = "serializers::from_str")]
+    height: u64,
+    #[serde(with = "serializers::bytes::hexstring")]
+    data_hash: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockId {
+    #[serde(with = "serializers::bytes::hexstring")]
+    hash: Vec<u8>,
+}
+
+impl HeightHeader {
+    /// The header of the block with hash `hash`, checked to be at `height`.
+    fn block_header(&self, hash: &[u8], height: u64) -> eyre::Result<BlockHeader> {
+        ensure!(
+            self.height == height,
+            "celestia block {height} returned as block {}",
+            self.height
+        );
+        Ok(BlockHeader {
+            height,
+            hash: B256::try_from(hash)
+                .map_err(|_| eyre!("invalid block hash length: {}", hash.len()))?,
+            data_hash: B256::try_from(self.data_hash.as_slice())
+                .map_err(|_| eyre!("invalid data hash length: {}", self.data_hash.len()))?,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ShareProof {
     #[serde(default)]
@@ -137,32 +259,86 @@ pub struct RowProof {
     pub proofs: Vec<MerkleProof>,
 }
 
+/// Timeout of connecting to a Celestia RPC.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Timeout of a whole request to a Celestia RPC, body included.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The client of the Celestia RPC requests, shared to reuse its connections.
+pub(crate) fn http_client() -> &'static reqwest::Client {
+    static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("the HTTP client should build")
+    });
+    &CLIENT
+}
+
 pub async fn get_celestia_tx_by_hash(rpc_url: &str, tx_hash: &[u8]) -> eyre::Result<GetTx> {
-    let tx = reqwest::get(format!(
-        "{rpc_url}/tx?hash={}&prove=true",
-        hex::encode_prefixed(tx_hash),
-    ))
-    .await?
-    .json::<RpcResponse<GetTx>>()
-    .await?
-    .result;
+    let tx = http_client()
+        .get(format!(
+            "{rpc_url}/tx?hash={}&prove=true",
+            hex::encode_prefixed(tx_hash),
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RpcResponse<GetTx>>()
+        .await?
+        .result;
     Ok(tx)
 }
 
+pub async fn get_celestia_block_data_hash(rpc_url: &str, height: u64) -> eyre::Result<B256> {
+    let data_hash = http_client()
+        .get(format!("{rpc_url}/block?height={height}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RpcResponse<GetBlock>>()
+        .await?
+        .result
+        .block
+        .header
+        .data_hash;
+    B256::try_from(data_hash.as_slice())
+        .map_err(|_| eyre!("invalid data hash length: {}", data_hash.len()))
+}
+
+pub async fn get_celestia_block_header(rpc_url: &str, height: u64) -> eyre::Result<BlockHeader> {
+    let block = http_client()
+        .get(format!("{rpc_url}/block?height={height}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RpcResponse<GetBlockHeader>>()
+        .await?
+        .result;
+    block
+        .block
+        .header
+        .block_header(&block.block_id.hash, height)
+}
+
 pub async fn get_celestia_data_root_inclusion_proof(
     rpc_url: &str,
     height: u64,
     start: u64,
     end: u64,
 ) -> eyre::Result<MerkleProof> {
-    let proof = reqwest::get(format!(
-        "{rpc_url}/data_root_inclusion_proof?height={height}&start={start}&end={end}",
-    ))
-    .await?
-    .json::<RpcResponse<GetDataRootInclusionProof>>()
-    .await?
-    .result
-    .proof;
+    let proof = http_client()
+        .get(format!(
+            "{rpc_url}/data_root_inclusion_proof?height={height}&start={start}&end={end}",
+        ))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RpcResponse<GetDataRootInclusionProof>>()
+        .await?
+        .result
+        .proof;
     Ok(proof)
 }
 
@@ -171,23 +347,97 @@ pub async fn find_commit_tx(
     ethereum_client: &impl Provider,
     tx_height: u64,
 ) -> eyre::Result<(B256, DataCommitmentStored)> {
-    let latest_eth_block_number = ethereum_client.get_block_number().await?;
-    let filter = blob_stream_contract
-        .DataCommitmentStored_filter()
-        .from_block(latest_eth_block_number - 50000)
-        .to_block(latest_eth_block_number);
-    let logs = filter.query().await?;
-    let mut tx_hash = Option::<B256>::None;
-    let mut event = Option::<DataCommitmentStored>::None;
-
-    for (e, log) in logs.into_iter() {
+    find_commit_tx_with(
+        blob_stream_contract,
+        ethereum_client,
+        tx_height,
+        Finality::Latest,
+    )
+    .await
+}
+
+/// Like [`find_commit_tx`], searching only the blocks of the given finality.
+pub async fn find_commit_tx_with(
+    blob_stream_contract: &SP1BlobstreamInstance<impl Provider>,
+    ethereum_client: &impl Provider,
+    tx_height: u64,
+    finality: Finality,
+) -> eyre::Result<(B256, DataCommitmentStored)> {
+    find_commitment_tx(
+        ethereum_client,
+        *blob_stream_contract.address(),
+        ContractVersion::Sp1Blobstream,
+        tx_height,
+        finality,
+    )
+    .await
+}
+
+/// The commitment covering `tx_height` among the events of the contract of `version` at
+/// `address`, and the tx which made it.
+pub async fn find_commitment_tx(
+    ethereum_client: &impl Provider,
+    address: Address,
+    version: ContractVersion,
+    tx_height: u64,
+    finality: Finality,
+) -> eyre::Result<(B256, DataCommitmentStored)> {
+    ensure!(
+        version != ContractVersion::QuantumGravityBridge,
+        "Quantum Gravity Bridge commitments carry no block range, the commitment covering \
+         celestia height {tx_height} cannot be located"
+    );
+    let last_eth_block_number = finality.last_block(ethereum_client).await?;
+    let filter = Filter::new()
+        .address(address)
+        .event_signature(version.commitment_event())
+        .from_block(last_eth_block_number.saturating_sub(50000))
+        .to_block(last_eth_block_number);
+    let logs = ethereum_client.get_logs(&filter).await?;
+
+    for log in logs {
+        let e = DataCommitmentStored::decode_log_data(log.data())?;
         if e.startBlock <= tx_height && e.endBlock > tx_height {
-            tx_hash = Some(log.transaction_hash.unwrap());
-            event = Some(e);
-            break;
+            let tx_hash = log
+                .transaction_hash
+                .ok_or_eyre("DataCommitmentStored log without transaction hash")?;
+            return Ok((tx_hash, e));
         }
     }
-    Ok((tx_hash.unwrap(), event.unwrap()))
+    Err(eyre!(
+        "no DataCommitmentStored event covering celestia height {tx_height} up to block \
+         {last_eth_block_number}"
+    ))
+}
+
+/// Fetches and assembles the guest input proving the shares of Celestia tx `tx_hash`, running the
+/// requests that do not depend on each other concurrently.
+pub async fn build_guest_input(
+    celestia: &impl CelestiaSource,
+    ethereum: &impl EthereumSource,
+    tx_hash: B256,
+) -> eyre::Result<GuestInput> {
+    let tx = celestia.get_tx(tx_hash).await?;
+    let (data_hash, (commit_tx_hash, event)) = tokio::try_join!(
+        celestia.get_block_data_hash(tx.height),
+        ethereum.find_commit(tx.height),
+    )?;
+    let (inclusion_proof, call) = tokio::try_join!(
+        celestia.get_data_root_inclusion_proof(tx.height, event.startBlock, event.endBlock),
+        ethereum.get_commit_call(commit_tx_hash, &event),
+    )?;
+
+    Ok(GuestInput {
+        commitHeaderRangeProof: call.proof,
+        commitHeaderRangePublicValues: call.publicValues,
+        sharesProof: SharesProof::new(
+            tx.height,
+            data_hash,
+            event.proofNonce,
+            inclusion_proof,
+            tx.proof,
+        )?,
+    })
 }
 
 mod vec_hexstring {
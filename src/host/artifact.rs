@@ -0,0 +1,187 @@
+//! SP1 Blobstream proofs read from local files instead of Ethereum calldata.
+//!
+//! Where the Blobstream updates are posted in EIP-4844 blobs or not posted on L1 at all, the
+//! `commitHeaderRange` proof comes from the prover network artifact. A [`ProofArtifact`] holds the
+//! proof, its public values, the program key it was proven for and the nonce the contract assigned
+//! to the commitment, which the public values do not carry.
+//!
+//! The proof is verified on the host against the program key of the verifier before use. The nonce
+//! is trusted input: nothing in the proof binds it, and the guest does not check it either. Where
+//! the commitment was posted to a contract, [`ProofArtifact::check_nonce`] checks it against the
+//! data commitment the contract stores for it.
+use crate::{
+    da_oracle::{DataCommitmentStored, SP1BlobstreamInstance, commitHeaderRangeCall},
+    guest::{self, Sp1Verifier},
+    host::{commit_call::commits, source::EthereumSource},
+    native::{self, ProofOutputs},
+};
+use alloy_primitives::{B256, Bytes, U256, hex, keccak256};
+use alloy_provider::Provider;
+use alloy_sol_types::SolValue;
+use eyre::{OptionExt, WrapErr, ensure, eyre};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// An SP1 proof of the SP1 Blobstream program, as stored in a JSON file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofArtifact {
+    pub vkey: B256,
+    pub proof_nonce: U256,
+    /// The proof prefixed with the first 4 bytes of the verifier hash, as sent on chain.
+    pub proof: Bytes,
+    pub public_values: Bytes,
+}
+
+impl ProofArtifact {
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).wrap_err_with(|| format!("{}", path.display()))?;
+        serde_json::from_slice(&bytes).wrap_err_with(|| format!("{}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn outputs(&self) -> eyre::Result<ProofOutputs> {
+        ProofOutputs::abi_decode(&self.public_values).wrap_err("invalid public values")
+    }
+
+    /// Checks that the proof is of the program and for the verifier `sp1` checks proofs against,
+    /// and verifies it.
+    pub fn validate(&self, sp1: Sp1Verifier) -> eyre::Result<()> {
+        let vkey = guest::program_vkey(sp1)?;
+        ensure!(
+            self.vkey == vkey,
+            "proof of program {}, the verifier expects {vkey}",
+            self.vkey
+        );
+        let verifier_hash = guest::verifier_hash(sp1)?;
+        let selector = self.proof.get(..4).ok_or_eyre("proof without selector")?;
+        ensure!(
+            verifier_hash[..4] == *selector,
+            "proof selector 0x{}, the verifier expects 0x{}",
+            hex::encode(selector),
+            hex::encode(&verifier_hash[..4])
+        );
+        let outputs = self.outputs()?;
+        ensure!(
+            outputs.trustedBlock < outputs.targetBlock,
+            "empty range {}..{}",
+            outputs.trustedBlock,
+            outputs.targetBlock
+        );
+        native::verify_commit_header_range(&self.public_values, &self.proof, sp1)
+    }
+
+    /// Checks that `contract` stores the data commitment of this proof under its nonce.
+    pub async fn check_nonce(
+        &self,
+        contract: &SP1BlobstreamInstance<impl Provider>,
+    ) -> eyre::Result<()> {
+        let stored = contract
+            .state_dataCommitments(self.proof_nonce)
+            .call()
+            .await?;
+        let data_commitment = self.outputs()?.dataCommitment;
+        ensure!(
+            stored == data_commitment,
+            "contract {} stores data commitment {stored} for nonce {}, the proof commits to \
+             {data_commitment}",
+            contract.address(),
+            self.proof_nonce
+        );
+        Ok(())
+    }
+
+    pub fn call(&self) -> commitHeaderRangeCall {
+        commitHeaderRangeCall {
+            proof: self.proof.clone(),
+            publicValues: self.public_values.clone(),
+        }
+    }
+
+    /// The event the contract would emit for this commitment.
+    pub fn event(&self) -> eyre::Result<DataCommitmentStored> {
+        let outputs = self.outputs()?;
+        Ok(DataCommitmentStored {
+            proofNonce: self.proof_nonce,
+            startBlock: outputs.trustedBlock,
+            endBlock: outputs.targetBlock,
+            dataCommitment: outputs.dataCommitment,
+        })
+    }
+
+    /// Stands for the commit tx hash, there is none.
+    pub fn id(&self) -> B256 {
+        keccak256(&self.proof)
+    }
+}
+
+/// Validated proof artifacts, as an Ethereum source.
+#[derive(Debug, Clone, Default)]
+pub struct ProofArtifacts {
+    pub artifacts: Vec<ProofArtifact>,
+}
+
+impl ProofArtifacts {
+    /// Loads the artifacts at `paths` and validates them against `sp1`.
+    pub fn load(
+        paths: impl IntoIterator<Item = impl AsRef<Path>>,
+        sp1: Sp1Verifier,
+    ) -> eyre::Result<Self> {
+        let artifacts = paths
+            .into_iter()
+            .map(|path| {
+                let artifact = ProofArtifact::load(&path)?;
+                artifact
+                    .validate(sp1)
+                    .wrap_err_with(|| format!("{}", path.as_ref().display()))?;
+                Ok(artifact)
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self { artifacts })
+    }
+
+    /// Checks the nonces of the artifacts against `contract`.
+    pub async fn check_nonces(
+        &self,
+        contract: &SP1BlobstreamInstance<impl Provider>,
+    ) -> eyre::Result<()> {
+        for artifact in &self.artifacts {
+            artifact.check_nonce(contract).await?;
+        }
+        Ok(())
+    }
+}
+
+impl EthereumSource for ProofArtifacts {
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        for artifact in &self.artifacts {
+            let event = artifact.event()?;
+            if event.startBlock <= height && event.endBlock > height {
+                return Ok((artifact.id(), event));
+            }
+        }
+        Err(eyre!("no proof artifact covers celestia height {height}"))
+    }
+
+    async fn get_commit_call(
+        &self,
+        _tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        self.artifacts
+            .iter()
+            .map(ProofArtifact::call)
+            .find(|call| commits(call, event))
+            .ok_or_else(|| {
+                eyre!(
+                    "no proof artifact commits blocks {}..{}",
+                    event.startBlock,
+                    event.endBlock
+                )
+            })
+    }
+}
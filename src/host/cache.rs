@@ -0,0 +1,322 @@
+//! On-disk cache of Blobstream commitments and data root inclusion proofs.
+//!
+//! Proving many blobs of the same Blobstream range fetches the same `DataCommitmentStored` event,
+//! `commitHeaderRange` calldata and inclusion proofs every time. [`CachedBlobstream`] keeps the
+//! events and their calls by contract address, range and nonce, along with the Ethereum block they
+//! were included in. The range and nonce name the file of an entry, so lookups only read the entry
+//! they need. Entries of blocks closer than `reorg_depth` to the head are checked with
+//! [`check_commit_tx`] and dropped if their tx was reorganized out. [`CachedCelestia`] keeps inclusion proofs by
+//! range and height, Celestia blocks being final.
+use crate::{
+    da_oracle::{DataCommitmentStored, SP1BlobstreamInstance, commitHeaderRangeCall},
+    host::{
+        BlockHeader, GetTx, MerkleProof, TxProof,
+        finality::{Reorged, check_commit_tx},
+        find_commit_tx,
+        source::{CelestiaSource, EthereumSource},
+    },
+    native::namespace::NamespaceProof,
+    verifier::Namespace,
+};
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_provider::Provider;
+use eyre::OptionExt;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::{Path, PathBuf};
+
+/// Default reorg depth, two epochs of Ethereum mainnet.
+pub const DEFAULT_REORG_DEPTH: u64 = 64;
+
+/// A `DataCommitmentStored` event with its `commitHeaderRange` call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedCommitment {
+    pub proof_nonce: U256,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub data_commitment: B256,
+    pub eth_tx_hash: B256,
+    pub eth_block_number: u64,
+    pub eth_block_hash: B256,
+    pub proof: Bytes,
+    pub public_values: Bytes,
+}
+
+impl CachedCommitment {
+    pub fn event(&self) -> DataCommitmentStored {
+        DataCommitmentStored {
+            proofNonce: self.proof_nonce,
+            startBlock: self.start_block,
+            endBlock: self.end_block,
+            dataCommitment: self.data_commitment,
+        }
+    }
+
+    pub fn call(&self) -> commitHeaderRangeCall {
+        commitHeaderRangeCall {
+            proof: self.proof.clone(),
+            publicValues: self.public_values.clone(),
+        }
+    }
+}
+
+/// The range and nonce of a cached commitment, which name its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryKey {
+    pub start_block: u64,
+    pub end_block: u64,
+    pub proof_nonce: U256,
+}
+
+impl EntryKey {
+    /// Parses the file name `{start_block}-{end_block}-{proof_nonce}.json`.
+    fn parse(file_name: &str) -> Option<Self> {
+        let mut parts = file_name.strip_suffix(".json")?.splitn(3, '-');
+        Some(Self {
+            start_block: parts.next()?.parse().ok()?,
+            end_block: parts.next()?.parse().ok()?,
+            proof_nonce: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedMerkleProof {
+    total: u64,
+    index: u64,
+    aunts: Vec<Bytes>,
+}
+
+fn read<T: DeserializeOwned>(path: &Path) -> eyre::Result<Option<T>> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Writes through a temporary file, so that concurrent readers never see a partial entry.
+fn write<T: Serialize>(path: &Path, value: &T) -> eyre::Result<()> {
+    std::fs::create_dir_all(path.parent().ok_or_eyre("cache entry without directory")?)?;
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// An SP1 Blobstream contract whose commitments are cached in `dir`.
+#[derive(Debug, Clone)]
+pub struct CachedBlobstream<P> {
+    pub contract: SP1BlobstreamInstance<P>,
+    pub dir: PathBuf,
+    pub reorg_depth: u64,
+}
+
+impl<P: Provider> CachedBlobstream<P> {
+    pub fn new(contract: SP1BlobstreamInstance<P>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            contract,
+            dir: dir.into(),
+            reorg_depth: DEFAULT_REORG_DEPTH,
+        }
+    }
+
+    fn commitments_dir(&self) -> PathBuf {
+        self.dir
+            .join(self.contract.address().to_string())
+            .join("commitments")
+    }
+
+    fn entry_path(&self, start_block: u64, end_block: u64, nonce: U256) -> PathBuf {
+        self.commitments_dir()
+            .join(format!("{start_block}-{end_block}-{nonce}.json"))
+    }
+
+    /// The ranges and nonces of the cached commitments, from the names of their files.
+    pub fn index(&self) -> eyre::Result<Vec<EntryKey>> {
+        let Ok(dir) = std::fs::read_dir(self.commitments_dir()) else {
+            return Ok(Vec::new());
+        };
+        let mut keys = Vec::new();
+        for file in dir {
+            if let Some(key) = file?.file_name().to_str().and_then(EntryKey::parse) {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    fn read_entry(&self, key: &EntryKey) -> eyre::Result<Option<CachedCommitment>> {
+        read(&self.entry_path(key.start_block, key.end_block, key.proof_nonce))
+    }
+
+    /// The cached commitments, in no particular order.
+    pub fn entries(&self) -> eyre::Result<Vec<CachedCommitment>> {
+        let mut entries = Vec::new();
+        for key in self.index()? {
+            entries.extend(self.read_entry(&key)?);
+        }
+        Ok(entries)
+    }
+
+    /// Whether `entry` is still on the canonical chain, it is removed from the cache if not.
+    async fn is_canonical(&self, entry: &CachedCommitment) -> eyre::Result<bool> {
+        let provider = self.contract.provider();
+        let head = provider.get_block_number().await?;
+        if head.saturating_sub(entry.eth_block_number) >= self.reorg_depth {
+            return Ok(true);
+        }
+        match check_commit_tx(provider, entry.eth_tx_hash, entry.eth_block_hash).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.is::<Reorged>() => {
+                let path = self.entry_path(entry.start_block, entry.end_block, entry.proof_nonce);
+                std::fs::remove_file(path)?;
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Fetches the commitment covering Celestia block `height` and caches it.
+    async fn fetch(&self, height: u64) -> eyre::Result<CachedCommitment> {
+        let provider = self.contract.provider();
+        let (eth_tx_hash, event) = find_commit_tx(&self.contract, provider, height).await?;
+        let receipt = provider
+            .get_transaction_receipt(eth_tx_hash)
+            .await?
+            .ok_or_eyre("commit tx receipt not found")?;
+        let call = EthereumSource::get_commit_call(&self.contract, eth_tx_hash, &event).await?;
+        let entry = CachedCommitment {
+            proof_nonce: event.proofNonce,
+            start_block: event.startBlock,
+            end_block: event.endBlock,
+            data_commitment: event.dataCommitment,
+            eth_tx_hash,
+            eth_block_number: receipt
+                .block_number
+                .ok_or_eyre("commit tx receipt without block number")?,
+            eth_block_hash: receipt
+                .block_hash
+                .ok_or_eyre("commit tx receipt without block hash")?,
+            proof: call.proof,
+            public_values: call.publicValues,
+        };
+        write(
+            &self.entry_path(entry.start_block, entry.end_block, entry.proof_nonce),
+            &entry,
+        )?;
+        Ok(entry)
+    }
+
+    /// The cached commitment covering Celestia block `height`, fetched if missing or reorganized.
+    /// Only the entry whose file name covers `height` is read.
+    pub async fn get(&self, height: u64) -> eyre::Result<CachedCommitment> {
+        let key = self
+            .index()?
+            .into_iter()
+            .find(|key| key.start_block <= height && key.end_block > height);
+        if let Some(key) = key
+            && let Some(entry) = self.read_entry(&key)?
+            && self.is_canonical(&entry).await?
+        {
+            return Ok(entry);
+        }
+        self.fetch(height).await
+    }
+
+    pub fn address(&self) -> Address {
+        *self.contract.address()
+    }
+}
+
+impl<P: Provider> EthereumSource for CachedBlobstream<P> {
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        let entry = self.get(height).await?;
+        Ok((entry.eth_tx_hash, entry.event()))
+    }
+
+    async fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        let path = self.entry_path(event.startBlock, event.endBlock, event.proofNonce);
+        if let Some(entry) = read::<CachedCommitment>(&path)?
+            && entry.eth_tx_hash == tx_hash
+            && self.is_canonical(&entry).await?
+        {
+            return Ok(entry.call());
+        }
+        EthereumSource::get_commit_call(&self.contract, tx_hash, event).await
+    }
+}
+
+/// A Celestia source whose data root inclusion proofs are cached in `dir`.
+#[derive(Debug, Clone)]
+pub struct CachedCelestia<S> {
+    pub source: S,
+    pub dir: PathBuf,
+}
+
+impl<S: CelestiaSource + Sync> CelestiaSource for CachedCelestia<S> {
+    async fn get_tx(&self, tx_hash: B256) -> eyre::Result<GetTx> {
+        self.source.get_tx(tx_hash).await
+    }
+
+    async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+        self.source.get_block_data_hash(height).await
+    }
+
+    async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        self.source.get_header(height).await
+    }
+
+    async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        let path = self
+            .dir
+            .join("inclusion_proofs")
+            .join(format!("{start}-{end}"))
+            .join(format!("{height}.json"));
+        if let Some(proof) = read::<CachedMerkleProof>(&path)? {
+            return Ok(MerkleProof {
+                total: proof.total,
+                index: proof.index,
+                aunts: proof.aunts.into_iter().map(Vec::from).collect(),
+            });
+        }
+        let proof = self
+            .source
+            .get_data_root_inclusion_proof(height, start, end)
+            .await?;
+        let cached = CachedMerkleProof {
+            total: proof.total,
+            index: proof.index,
+            aunts: proof.aunts.iter().cloned().map(Bytes::from).collect(),
+        };
+        write(&path, &cached)?;
+        Ok(proof)
+    }
+
+    async fn get_blob_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<TxProof> {
+        self.source
+            .get_blob_proof(height, namespace, commitment)
+            .await
+    }
+
+    async fn get_namespace_shares(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> eyre::Result<NamespaceProof> {
+        self.source.get_namespace_shares(height, namespace).await
+    }
+}
@@ -0,0 +1,416 @@
+//! Client of the JSON-RPC API of celestia-node light and bridge nodes.
+//!
+//! A node has no lookup of txs by hash, blobs are found by height, namespace and commitment. The
+//! proof of their shares is assembled in the same [`TxProof`] as the CometBFT `/tx` endpoint
+//! returns, the row proofs being computed from the data availability header of the block.
+use crate::{
+    host::{
+        BlockHeader, BlockId, HeightHeader, MerkleProof, RowProof, ShareProof, TxProof, http_client,
+    },
+    native::{
+        binary_merkle,
+        namespace::{self, NamespaceProof, NamespaceRow},
+        shares::Blob,
+    },
+    verifier::{Namespace, NamespaceMerkleMultiproof, NamespaceNode},
+};
+use alloy_primitives::{B256, Bytes, U256};
+use eyre::{OptionExt, bail, ensure, eyre};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tendermint::serializers;
+
+#[derive(Debug, Clone)]
+pub struct CelestiaNodeClient {
+    url: String,
+    /// Bearer token of the node API, as created by `celestia <node> auth read`.
+    auth_token: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct Request<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+/// Bytes serialized as base64, as the node expects namespaces and commitments.
+#[derive(Debug, Serialize)]
+pub(crate) struct Base64(#[serde(with = "serializers::bytes::base64string")] Vec<u8>);
+
+#[derive(Debug, Deserialize)]
+pub struct NodeBlob {
+    #[serde(with = "serializers::bytes::base64string")]
+    pub namespace: Vec<u8>,
+    #[serde(with = "serializers::bytes::base64string")]
+    pub data: Vec<u8>,
+    pub share_version: u8,
+    #[serde(with = "serializers::bytes::base64string")]
+    pub commitment: Vec<u8>,
+    /// Index of the first share of the blob in the original square.
+    pub index: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtendedHeader {
+    header: HeightHeader,
+    commit: NodeCommit,
+    dah: DataAvailabilityHeader,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeCommit {
+    block_id: BlockId,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataAvailabilityHeader {
+    #[serde(with = "serializers::bytes::vec_base64string")]
+    row_roots: Vec<Vec<u8>>,
+    #[serde(with = "serializers::bytes::vec_base64string")]
+    column_roots: Vec<Vec<u8>>,
+}
+
+/// A merkle proof as serialized by the node, with numbers and base64 nodes.
+#[derive(Debug, Deserialize)]
+struct NodeMerkleProof {
+    total: u64,
+    index: u64,
+    #[serde(default, with = "serializers::bytes::vec_base64string")]
+    aunts: Vec<Vec<u8>>,
+}
+
+/// The shares of a namespace in a row, as returned by `share.GetNamespaceData`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct NamespacedRow {
+    #[serde(default, with = "serializers::bytes::vec_base64string")]
+    shares: Vec<Vec<u8>>,
+    proof: NodeNamespaceProof,
+}
+
+/// A namespace merkle proof as serialized by the node. `leaf_hash` is set in absence proofs.
+#[derive(Debug, Deserialize)]
+struct NodeNamespaceProof {
+    #[serde(default)]
+    start: u64,
+    end: u64,
+    #[serde(default, with = "serializers::bytes::vec_base64string")]
+    nodes: Vec<Vec<u8>>,
+    #[serde(default, with = "serializers::bytes::base64string")]
+    leaf_hash: Vec<u8>,
+}
+
+impl TryFrom<NodeNamespaceProof> for NamespaceMerkleMultiproof {
+    type Error = eyre::Error;
+
+    fn try_from(proof: NodeNamespaceProof) -> eyre::Result<Self> {
+        Ok(Self {
+            beginKey: U256::from(proof.start),
+            endKey: U256::from(proof.end),
+            sideNodes: proof
+                .nodes
+                .iter()
+                .map(|node| NamespaceNode::try_from(node.as_slice()))
+                .collect::<eyre::Result<_>>()?,
+        })
+    }
+}
+
+impl From<NodeMerkleProof> for MerkleProof {
+    fn from(proof: NodeMerkleProof) -> Self {
+        Self {
+            total: proof.total,
+            index: proof.index,
+            aunts: proof.aunts,
+        }
+    }
+}
+
+impl ExtendedHeader {
+    pub fn data_hash(&self) -> eyre::Result<B256> {
+        B256::try_from(self.header.data_hash.as_slice())
+            .map_err(|_| eyre!("invalid data hash length: {}", self.header.data_hash.len()))
+    }
+
+    /// The header of block `height`, failing if this is the header of another block.
+    pub fn block_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        self.header.block_header(&self.commit.block_id.hash, height)
+    }
+}
+
+/// The params of `share.GetNamespaceData`.
+pub(crate) fn namespace_data_params(height: u64, namespace: &Namespace) -> (u64, Base64) {
+    (height, Base64(namespace.to_bytes().to_vec()))
+}
+
+impl CelestiaNodeClient {
+    pub fn new(url: impl Into<String>, auth_token: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            auth_token,
+            client: http_client().clone(),
+        }
+    }
+
+    async fn call<P: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> eyre::Result<T> {
+        let mut request = self.client.post(&self.url).json(&Request {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        });
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Response<T>>()
+            .await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => bail!("{method} failed ({}): {}", error.code, error.message),
+            (Some(result), None) => Ok(result),
+            (None, None) => bail!("{method} returned no result"),
+        }
+    }
+
+    pub async fn get_blob(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<NodeBlob> {
+        self.call(
+            "blob.Get",
+            (
+                height,
+                Base64(namespace.to_bytes().to_vec()),
+                Base64(commitment.to_vec()),
+            ),
+        )
+        .await
+    }
+
+    /// The namespace merkle proofs of the shares of a blob, one per row.
+    pub async fn get_blob_share_proofs(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<Vec<ShareProof>> {
+        self.call(
+            "blob.GetProof",
+            (
+                height,
+                Base64(namespace.to_bytes().to_vec()),
+                Base64(commitment.to_vec()),
+            ),
+        )
+        .await
+    }
+
+    pub async fn get_header(&self, height: u64) -> eyre::Result<ExtendedHeader> {
+        self.call("header.GetByHeight", (height,)).await
+    }
+
+    pub async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        let proof: NodeMerkleProof = self
+            .call(
+                "blobstream.GetDataRootTupleInclusionProof",
+                (height, start, end),
+            )
+            .await?;
+        Ok(proof.into())
+    }
+
+    /// The proof of the shares of a blob, along with the data hash of its block.
+    pub async fn get_blob_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<(B256, TxProof)> {
+        let blob = self.get_blob(height, namespace, commitment).await?;
+        let share_proofs = self
+            .get_blob_share_proofs(height, namespace, commitment)
+            .await?;
+        let header = self.get_header(height).await?;
+        let data_hash = header.data_hash()?;
+        Ok((data_hash, blob_proof(blob, share_proofs, header)?))
+    }
+
+    /// The shares of a namespace in the rows which may hold it, or the proof of its absence.
+    async fn get_namespace_data(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> eyre::Result<Vec<NamespacedRow>> {
+        self.call(
+            "share.GetNamespaceData",
+            namespace_data_params(height, namespace),
+        )
+        .await
+    }
+
+    /// The proof of all the shares of a namespace in a block, or of their absence, along with the
+    /// data hash of the block.
+    pub async fn get_namespace_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> eyre::Result<(B256, NamespaceProof)> {
+        let (header, rows) = tokio::try_join!(
+            self.get_header(height),
+            self.get_namespace_data(height, namespace)
+        )?;
+        let data_hash = header.data_hash()?;
+        Ok((data_hash, namespace_proof(namespace, rows, header)?))
+    }
+}
+
+/// Assembles the proof of the shares of `blob`, from its namespace merkle proofs and the header of
+/// its block.
+fn blob_proof(
+    blob: NodeBlob,
+    share_proofs: Vec<ShareProof>,
+    header: ExtendedHeader,
+) -> eyre::Result<TxProof> {
+    ensure!(
+        blob.share_version == 0,
+        "unsupported share version {}",
+        blob.share_version
+    );
+    let namespace = Namespace::try_from(blob.namespace.as_slice())?;
+    let start = u64::try_from(blob.index).map_err(|_| eyre!("blob without share index"))? as usize;
+    let shares = Blob {
+        namespace: namespace.clone(),
+        data: blob.data,
+    }
+    .to_shares();
+
+    let dah = header.dah;
+    let width = dah.row_roots.len() / 2;
+    ensure!(width > 0, "empty data availability header");
+    let rows = start / width..(start + shares.len()).div_ceil(width);
+    ensure!(
+        rows.end <= width && share_proofs.len() == rows.len(),
+        "blob shares do not match the square"
+    );
+
+    let leaves = dah
+        .row_roots
+        .iter()
+        .chain(&dah.column_roots)
+        .collect::<Vec<_>>();
+    let data_root = binary_merkle::compute_root_from_leaves(&leaves);
+    ensure!(
+        data_root.as_slice() == header.header.data_hash.as_slice(),
+        "data availability header does not match the data hash"
+    );
+
+    Ok(TxProof {
+        data: shares,
+        share_proofs,
+        namespace_id: namespace.id.to_vec(),
+        row_proof: RowProof {
+            row_roots: dah.row_roots[rows.clone()].to_vec(),
+            proofs: rows
+                .map(|row| binary_merkle::prove(&leaves, row).try_into())
+                .collect::<eyre::Result<_>>()?,
+        },
+        namespace_version: namespace.version[0],
+    })
+}
+
+/// Assembles the proof of `namespace` from the rows the node returned for it, adding the rows
+/// around them from the header of the block.
+pub(crate) fn namespace_proof(
+    namespace: &Namespace,
+    rows: Vec<NamespacedRow>,
+    header: ExtendedHeader,
+) -> eyre::Result<NamespaceProof> {
+    let dah = header.dah;
+    let leaves = dah
+        .row_roots
+        .iter()
+        .chain(&dah.column_roots)
+        .collect::<Vec<_>>();
+    let data_root = binary_merkle::compute_root_from_leaves(&leaves);
+    ensure!(
+        data_root.as_slice() == header.header.data_hash.as_slice(),
+        "data availability header does not match the data hash"
+    );
+    let row_roots = dah
+        .row_roots
+        .iter()
+        .map(|root| NamespaceNode::try_from(root.as_slice()))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    ensure!(!row_roots.is_empty(), "empty data availability header");
+
+    let (covering, proven) = namespace::namespace_rows(&row_roots, namespace);
+    ensure!(
+        rows.len() == covering.len(),
+        "{} namespace rows, the square has {} rows holding the namespace",
+        rows.len(),
+        covering.len()
+    );
+    let mut rows = rows.into_iter();
+    let rows = proven
+        .map(|row| {
+            let mut proven_row = NamespaceRow {
+                root: row_roots[row].clone(),
+                root_proof: binary_merkle::prove(&leaves, row),
+                proof: NamespaceMerkleMultiproof {
+                    beginKey: U256::ZERO,
+                    endKey: U256::ZERO,
+                    sideNodes: Vec::new(),
+                },
+                shares: Vec::new(),
+                absence_leaf: None,
+            };
+            if !covering.contains(&row) {
+                return Ok(proven_row);
+            }
+            let node_row = rows.next().ok_or_eyre("missing namespace row")?;
+            if node_row.shares.is_empty() {
+                ensure!(
+                    !node_row.proof.leaf_hash.is_empty(),
+                    "row {row} has neither shares nor absence proof"
+                );
+                proven_row.absence_leaf = Some(NamespaceNode::try_from(
+                    node_row.proof.leaf_hash.as_slice(),
+                )?);
+            }
+            proven_row.shares = node_row.shares.into_iter().map(Bytes::from).collect();
+            proven_row.proof = node_row.proof.try_into()?;
+            Ok(proven_row)
+        })
+        .collect::<eyre::Result<_>>()?;
+    Ok(NamespaceProof {
+        namespace: namespace.clone(),
+        rows,
+    })
+}
@@ -0,0 +1,29 @@
+//! Checks of the fetched data before proving, so that a malicious or buggy endpoint is caught at
+//! fetch time rather than by the prover.
+use crate::{GuestInput, host::source::CelestiaSource, native};
+use eyre::{WrapErr, ensure, eyre};
+
+/// Checks the merkle proofs of `input` natively, everything the verifier checks but the SP1
+/// proof itself.
+pub fn check_proofs(input: &GuestInput) -> eyre::Result<()> {
+    native::verify_skipping_sp1(input).wrap_err("guest input rejected")?;
+    Ok(())
+}
+
+/// Checks the proofs of `input`, and that `second` agrees on the data hash of the attested block.
+pub async fn cross_check(input: &GuestInput, second: &impl CelestiaSource) -> eyre::Result<()> {
+    check_proofs(input)?;
+    let tuple = &input.sharesProof.attestationProof.tuple;
+    let height = u64::try_from(tuple.height)
+        .map_err(|_| eyre!("celestia height {} above 64 bits", tuple.height))?;
+    let data_hash = second
+        .get_block_data_hash(height)
+        .await
+        .wrap_err("second source")?;
+    ensure!(
+        data_hash == tuple.dataRoot,
+        "data hash of celestia block {height} differs between sources: {} and {data_hash}",
+        tuple.dataRoot
+    );
+    Ok(())
+}
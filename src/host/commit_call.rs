@@ -0,0 +1,372 @@
+//! Location of the `commitHeaderRange` call which emitted a `DataCommitmentStored` event.
+//!
+//! Relayers do not always call SP1 Blobstream directly, but through a Safe, a multicall contract
+//! or an ERC-4337 entry point. The calldata of these known wrappers is unpacked, calls made through
+//! any other contract are found in the call trace of the tx (`debug_traceTransaction`). Among the
+//! calls to the contract, the one whose public values match the event is picked, or for Blobstream X
+//! the one whose target block ends the range of the event.
+use crate::{
+    da_oracle::{BlobstreamX, DataCommitmentStored, commitHeaderRangeCall},
+    host::deployments::ContractVersion,
+    native::ProofOutputs,
+};
+use alloy_consensus::Transaction;
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_provider::Provider;
+use alloy_sol_types::{SolCall, SolValue, sol};
+use eyre::{OptionExt, WrapErr, bail};
+use serde::Deserialize;
+use serde_json::json;
+
+sol! {
+    interface Safe {
+        function execTransaction(
+            address to,
+            uint256 value,
+            bytes data,
+            uint8 operation,
+            uint256 safeTxGas,
+            uint256 baseGas,
+            uint256 gasPrice,
+            address gasToken,
+            address refundReceiver,
+            bytes signatures
+        ) external payable returns (bool success);
+    }
+
+    interface MultiSend {
+        function multiSend(bytes transactions) external payable;
+    }
+
+    interface Multicall3 {
+        struct Call {
+            address target;
+            bytes callData;
+        }
+
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Call3Value {
+            address target;
+            bool allowFailure;
+            uint256 value;
+            bytes callData;
+        }
+
+        function aggregate(Call[] calls) external payable;
+        function tryAggregate(bool requireSuccess, Call[] calls) external payable;
+        function aggregate3(Call3[] calls) external payable;
+        function aggregate3Value(Call3Value[] calls) external payable;
+    }
+
+    interface Multicall {
+        function multicall(bytes[] data) external payable;
+    }
+
+    interface EntryPointV06 {
+        struct UserOperation {
+            address sender;
+            uint256 nonce;
+            bytes initCode;
+            bytes callData;
+            uint256 callGasLimit;
+            uint256 verificationGasLimit;
+            uint256 preVerificationGas;
+            uint256 maxFeePerGas;
+            uint256 maxPriorityFeePerGas;
+            bytes paymasterAndData;
+            bytes signature;
+        }
+
+        function handleOps(UserOperation[] ops, address beneficiary) external;
+    }
+
+    interface EntryPointV07 {
+        struct PackedUserOperation {
+            address sender;
+            uint256 nonce;
+            bytes initCode;
+            bytes callData;
+            bytes32 accountGasLimits;
+            uint256 preVerificationGas;
+            bytes32 gasFees;
+            bytes paymasterAndData;
+            bytes signature;
+        }
+
+        function handleOps(PackedUserOperation[] ops, address beneficiary) external;
+    }
+
+    interface SimpleAccount {
+        function execute(address dest, uint256 value, bytes func) external;
+        function executeBatch(address[] dest, bytes[] func) external;
+    }
+
+    interface SimpleAccountV07 {
+        function executeBatch(address[] dest, uint256[] value, bytes[] func) external;
+    }
+
+    interface Safe4337Module {
+        function executeUserOp(address to, uint256 value, bytes data, uint8 operation) external;
+    }
+}
+
+/// Nesting of wrappers unpacked at most.
+const MAX_DEPTH: usize = 8;
+
+/// The calls wrapped by a call of `input` to `to`, one level deep.
+fn unwrap_call(to: Address, input: &[u8]) -> Vec<(Address, Bytes)> {
+    if let Ok(call) = Safe::execTransactionCall::abi_decode(input) {
+        return vec![(call.to, call.data)];
+    }
+    if let Ok(call) = MultiSend::multiSendCall::abi_decode(input) {
+        return multi_send(&call.transactions);
+    }
+    if let Ok(call) = Multicall3::aggregateCall::abi_decode(input) {
+        return call
+            .calls
+            .into_iter()
+            .map(|call| (call.target, call.callData))
+            .collect();
+    }
+    if let Ok(call) = Multicall3::tryAggregateCall::abi_decode(input) {
+        return call
+            .calls
+            .into_iter()
+            .map(|call| (call.target, call.callData))
+            .collect();
+    }
+    if let Ok(call) = Multicall3::aggregate3Call::abi_decode(input) {
+        return call
+            .calls
+            .into_iter()
+            .map(|call| (call.target, call.callData))
+            .collect();
+    }
+    if let Ok(call) = Multicall3::aggregate3ValueCall::abi_decode(input) {
+        return call
+            .calls
+            .into_iter()
+            .map(|call| (call.target, call.callData))
+            .collect();
+    }
+    if let Ok(call) = Multicall::multicallCall::abi_decode(input) {
+        // calls to the contract itself
+        return call.data.into_iter().map(|data| (to, data)).collect();
+    }
+    if let Ok(call) = EntryPointV06::handleOpsCall::abi_decode(input) {
+        return call
+            .ops
+            .into_iter()
+            .map(|op| (op.sender, op.callData))
+            .collect();
+    }
+    if let Ok(call) = EntryPointV07::handleOpsCall::abi_decode(input) {
+        return call
+            .ops
+            .into_iter()
+            .map(|op| (op.sender, op.callData))
+            .collect();
+    }
+    if let Ok(call) = SimpleAccount::executeCall::abi_decode(input) {
+        return vec![(call.dest, call.func)];
+    }
+    if let Ok(call) = SimpleAccount::executeBatchCall::abi_decode(input) {
+        return call.dest.into_iter().zip(call.func).collect();
+    }
+    if let Ok(call) = SimpleAccountV07::executeBatchCall::abi_decode(input) {
+        return call.dest.into_iter().zip(call.func).collect();
+    }
+    if let Ok(call) = Safe4337Module::executeUserOpCall::abi_decode(input) {
+        return vec![(call.to, call.data)];
+    }
+    Vec::new()
+}
+
+/// The calls of a `multiSend`, packed as operation (1 byte), to (20), value (32), data length (32)
+/// and data.
+fn multi_send(mut transactions: &[u8]) -> Vec<(Address, Bytes)> {
+    let mut calls = Vec::new();
+    while transactions.len() >= 85 {
+        let to = Address::from_slice(&transactions[1..21]);
+        let Ok(len) = usize::try_from(U256::from_be_slice(&transactions[53..85])) else {
+            break;
+        };
+        let Some(data) = 85usize
+            .checked_add(len)
+            .and_then(|end| transactions.get(85..end))
+        else {
+            break;
+        };
+        calls.push((to, Bytes::copy_from_slice(data)));
+        transactions = &transactions[85 + len..];
+    }
+    calls
+}
+
+/// The call of `input` to `to` and the calls it wraps, recursively, as `(to, input)` pairs.
+pub fn wrapped_calls(to: Address, input: &[u8]) -> Vec<(Address, Bytes)> {
+    let mut calls = vec![(to, Bytes::copy_from_slice(input))];
+    let mut level = 0..1;
+    for _ in 0..MAX_DEPTH {
+        let wrapped: Vec<_> = calls[level.clone()]
+            .iter()
+            .flat_map(|(to, input)| unwrap_call(*to, input))
+            .collect();
+        if wrapped.is_empty() {
+            break;
+        }
+        level = calls.len()..calls.len() + wrapped.len();
+        calls.extend(wrapped);
+    }
+    calls
+}
+
+/// Whether `call` committed the range of `event`.
+pub fn commits(call: &commitHeaderRangeCall, event: &DataCommitmentStored) -> bool {
+    ProofOutputs::abi_decode(&call.publicValues).is_ok_and(|outputs| {
+        outputs.dataCommitment == event.dataCommitment
+            && outputs.trustedBlock == event.startBlock
+            && outputs.targetBlock == event.endBlock
+    })
+}
+
+/// Whether `input`, a call to a contract of `version`, committed the range of `event`. Blobstream X
+/// calls only name the end of the range.
+pub fn commits_input(version: ContractVersion, input: &[u8], event: &DataCommitmentStored) -> bool {
+    if !input.starts_with(&version.commit_selector()) {
+        return false;
+    }
+    match version {
+        ContractVersion::Sp1Blobstream => {
+            commitHeaderRangeCall::abi_decode(input).is_ok_and(|call| commits(&call, event))
+        }
+        ContractVersion::BlobstreamX => BlobstreamX::commitHeaderRangeCall::abi_decode(input)
+            .is_ok_and(|call| call._targetBlock == event.endBlock),
+        ContractVersion::QuantumGravityBridge => false,
+    }
+}
+
+/// The input of the call to `contract`, of `version`, committing `event` among `calls`.
+fn find_commit(
+    calls: impl IntoIterator<Item = (Address, Bytes)>,
+    contract: Address,
+    version: ContractVersion,
+    event: &DataCommitmentStored,
+) -> Option<Bytes> {
+    calls
+        .into_iter()
+        .find(|(to, input)| *to == contract && commits_input(version, input, event))
+        .map(|(_, input)| input)
+}
+
+/// The input of the call to `contract`, of `version`, committing `event` in the calldata of a tx
+/// to `to`, unpacking the known wrappers.
+pub fn find_in_calldata(
+    contract: Address,
+    version: ContractVersion,
+    to: Address,
+    input: &[u8],
+    event: &DataCommitmentStored,
+) -> Option<Bytes> {
+    find_commit(wrapped_calls(to, input), contract, version, event)
+}
+
+/// A frame of the `callTracer` of `debug_traceTransaction`.
+#[derive(Debug, Deserialize)]
+struct CallFrame {
+    to: Option<Address>,
+    input: Bytes,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// The calls of this frame and its subcalls which did not revert.
+    fn flatten(self, calls: &mut Vec<(Address, Bytes)>) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Some(to) = self.to {
+            calls.push((to, self.input));
+        }
+        for frame in self.calls {
+            frame.flatten(calls);
+        }
+    }
+}
+
+/// The input of the call to `contract`, of `version`, committing `event` in the call trace of tx
+/// `tx_hash`.
+pub async fn find_in_trace(
+    provider: &impl Provider,
+    contract: Address,
+    version: ContractVersion,
+    tx_hash: B256,
+    event: &DataCommitmentStored,
+) -> eyre::Result<Option<Bytes>> {
+    let trace: CallFrame = provider
+        .raw_request(
+            "debug_traceTransaction".into(),
+            (tx_hash, json!({ "tracer": "callTracer" })),
+        )
+        .await?;
+    let mut calls = Vec::new();
+    trace.flatten(&mut calls);
+    Ok(find_commit(calls, contract, version, event))
+}
+
+/// The input of the call to `contract`, of `version`, committing `event` in tx `tx_hash`, from its
+/// calldata or else its call trace.
+pub async fn find_commit_input(
+    provider: &impl Provider,
+    contract: Address,
+    version: ContractVersion,
+    tx_hash: B256,
+    event: &DataCommitmentStored,
+) -> eyre::Result<Bytes> {
+    let tx = provider
+        .get_transaction_by_hash(tx_hash)
+        .await?
+        .ok_or_eyre("commit tx not found")?;
+    if let Some(to) = tx.to()
+        && let Some(input) = find_in_calldata(contract, version, to, tx.input(), event)
+    {
+        return Ok(input);
+    }
+    match find_in_trace(provider, contract, version, tx_hash, event)
+        .await
+        .wrap_err_with(|| format!("commitHeaderRange call not in the calldata of {tx_hash}"))?
+    {
+        Some(input) => Ok(input),
+        None => bail!(
+            "no commitHeaderRange call of {contract} committing blocks {}..{} in {tx_hash}",
+            event.startBlock,
+            event.endBlock
+        ),
+    }
+}
+
+/// The SP1 Blobstream call to `contract` committing `event` in tx `tx_hash`.
+pub async fn find_commit_call(
+    provider: &impl Provider,
+    contract: Address,
+    tx_hash: B256,
+    event: &DataCommitmentStored,
+) -> eyre::Result<commitHeaderRangeCall> {
+    let input = find_commit_input(
+        provider,
+        contract,
+        ContractVersion::Sp1Blobstream,
+        tx_hash,
+        event,
+    )
+    .await?;
+    Ok(commitHeaderRangeCall::abi_decode(&input)?)
+}
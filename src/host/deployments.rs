@@ -0,0 +1,191 @@
+//! Known Blobstream deployments, by chain.
+//!
+//! The SP1 Blobstream contracts are proxies which ran Blobstream X before, their older
+//! commitments are `DataCommitmentStored` events of the same signature made by `BlobstreamX`
+//! calls. [`LEGACY_DEPLOYMENTS`] lists these Blobstream X deployments, whose commitments are located
+//! through the event and function of their version. Only the commitments of SP1 Blobstream are
+//! verified by the guest.
+use crate::{
+    da_oracle::{
+        self, BlobstreamX, DataCommitmentStored, QuantumGravityBridge, SP1BlobstreamInstance,
+    },
+    host::{commit_call::find_commit_input, finality::Finality, find_commitment_tx},
+};
+use alloy_primitives::{Address, B256, Bytes, address};
+use alloy_provider::Provider;
+use alloy_sol_types::{SolCall, SolEvent};
+use eyre::{ensure, eyre};
+
+/// A Blobstream implementation, with the signatures of its commitment event and function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContractVersion {
+    Sp1Blobstream,
+    BlobstreamX,
+    QuantumGravityBridge,
+}
+
+impl ContractVersion {
+    /// The topic of the event emitted for each commitment.
+    pub fn commitment_event(&self) -> B256 {
+        match self {
+            Self::Sp1Blobstream => DataCommitmentStored::SIGNATURE_HASH,
+            Self::BlobstreamX => BlobstreamX::DataCommitmentStored::SIGNATURE_HASH,
+            Self::QuantumGravityBridge => {
+                QuantumGravityBridge::DataRootTupleRootEvent::SIGNATURE_HASH
+            }
+        }
+    }
+
+    /// The selector of the function committing a range of headers.
+    pub fn commit_selector(&self) -> [u8; 4] {
+        match self {
+            Self::Sp1Blobstream => da_oracle::commitHeaderRangeCall::SELECTOR,
+            Self::BlobstreamX => BlobstreamX::commitHeaderRangeCall::SELECTOR,
+            Self::QuantumGravityBridge => {
+                QuantumGravityBridge::submitDataRootTupleRootCall::SELECTOR
+            }
+        }
+    }
+
+    /// Whether the guest verifies the commitments of this version.
+    pub fn is_verifiable(&self) -> bool {
+        *self == Self::Sp1Blobstream
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deployment {
+    pub chain: &'static str,
+    pub chain_id: u64,
+    pub address: Address,
+    pub version: ContractVersion,
+    /// Versions the contract ran before, oldest first.
+    pub previous_versions: &'static [ContractVersion],
+}
+
+impl Deployment {
+    /// The contract behind `provider`, for SP1 Blobstream deployments.
+    pub fn sp1_blobstream<P: Provider>(
+        &self,
+        provider: P,
+    ) -> eyre::Result<SP1BlobstreamInstance<P>> {
+        ensure!(
+            self.version == ContractVersion::Sp1Blobstream,
+            "{:?} deployment at {} on {} is not SP1 Blobstream",
+            self.version,
+            self.address,
+            self.chain
+        );
+        Ok(SP1BlobstreamInstance::new(self.address, provider))
+    }
+
+    /// The commitment of this contract covering celestia height `height`, in the blocks of the
+    /// given finality, and the tx which made it.
+    pub async fn find_commit_tx(
+        &self,
+        provider: &impl Provider,
+        height: u64,
+        finality: Finality,
+    ) -> eyre::Result<(B256, DataCommitmentStored)> {
+        find_commitment_tx(provider, self.address, self.version, height, finality).await
+    }
+
+    /// The input of the call to this contract committing `event` in tx `tx_hash`.
+    pub async fn find_commit_input(
+        &self,
+        provider: &impl Provider,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<Bytes> {
+        find_commit_input(provider, self.address, self.version, tx_hash, event).await
+    }
+}
+
+const fn sp1_blobstream(chain: &'static str, chain_id: u64, address: Address) -> Deployment {
+    Deployment {
+        chain,
+        chain_id,
+        address,
+        version: ContractVersion::Sp1Blobstream,
+        previous_versions: &[ContractVersion::BlobstreamX],
+    }
+}
+
+pub const ETHEREUM: Deployment = sp1_blobstream(
+    "ethereum",
+    1,
+    address!("0x7Cf3876F681Dbb6EdA8f6FfC45D66B996Df08fAe"),
+);
+pub const ARBITRUM_ONE: Deployment = sp1_blobstream(
+    "arbitrum-one",
+    42161,
+    address!("0xA83ca7775Bc2889825BcDeDfFa5b758cf69e8794"),
+);
+pub const BASE: Deployment = sp1_blobstream(
+    "base",
+    8453,
+    address!("0xA83ca7775Bc2889825BcDeDfFa5b758cf69e8794"),
+);
+pub const SEPOLIA: Deployment = sp1_blobstream(
+    "sepolia",
+    11155111,
+    address!("0xf0c6429ebAB2e7DC6e05DaFB61128bE21f13cb1e"),
+);
+pub const ARBITRUM_SEPOLIA: Deployment = sp1_blobstream(
+    "arbitrum-sepolia",
+    421614,
+    address!("0xc3e209eb245Fd59c8586777b499d6A665DF3ABD2"),
+);
+pub const BASE_SEPOLIA: Deployment = sp1_blobstream(
+    "base-sepolia",
+    84532,
+    address!("0xc3e209eb245Fd59c8586777b499d6A665DF3ABD2"),
+);
+
+pub const DEPLOYMENTS: &[Deployment] = &[
+    ETHEREUM,
+    ARBITRUM_ONE,
+    BASE,
+    SEPOLIA,
+    ARBITRUM_SEPOLIA,
+    BASE_SEPOLIA,
+];
+
+/// The Blobstream X implementation of an SP1 Blobstream proxy, before its upgrade.
+const fn blobstream_x(deployment: Deployment) -> Deployment {
+    Deployment {
+        version: ContractVersion::BlobstreamX,
+        previous_versions: &[],
+        ..deployment
+    }
+}
+
+pub const LEGACY_DEPLOYMENTS: &[Deployment] = &[
+    blobstream_x(ETHEREUM),
+    blobstream_x(ARBITRUM_ONE),
+    blobstream_x(BASE),
+    blobstream_x(SEPOLIA),
+    blobstream_x(ARBITRUM_SEPOLIA),
+    blobstream_x(BASE_SEPOLIA),
+];
+
+/// The known deployment on chain `chain_id`.
+pub fn by_chain_id(chain_id: u64) -> Option<&'static Deployment> {
+    DEPLOYMENTS
+        .iter()
+        .find(|deployment| deployment.chain_id == chain_id)
+}
+
+/// The current and legacy deployments on chain `chain_id`.
+pub fn all_by_chain_id(chain_id: u64) -> impl Iterator<Item = &'static Deployment> {
+    DEPLOYMENTS
+        .iter()
+        .chain(LEGACY_DEPLOYMENTS)
+        .filter(move |deployment| deployment.chain_id == chain_id)
+}
+
+/// The known deployment on the chain of `provider`.
+pub async fn locate(provider: &impl Provider) -> eyre::Result<&'static Deployment> {
+    let chain_id = provider.get_chain_id().await?;
+    by_chain_id(chain_id).ok_or_else(|| eyre!("no known Blobstream deployment on chain {chain_id}"))
+}
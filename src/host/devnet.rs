@@ -0,0 +1,93 @@
+//! Blobstream data commitments computed by the host, for devnets without an SP1 Blobstream relayer.
+//!
+//! The commitments are merkle roots of the data root tuples of a Celestia devnet, attested with a
+//! mock SP1 proof that only the mock SP1 verifier with the same program key accepts.
+use crate::{
+    GuestInput,
+    da_oracle::DataCommitmentStored,
+    guest::Sp1Verifier,
+    host::{get_celestia_block_data_hash, get_celestia_tx_by_hash},
+    synthetic::{DataCommitment, sp1_mock_proof},
+    verifier::SharesProof,
+};
+use alloy_primitives::{B256, U256};
+use eyre::{OptionExt, ensure};
+use std::ops::Range;
+
+/// A Celestia devnet whose data commitments are consecutive ranges of `range_len` blocks, the first
+/// one starting at block 1.
+#[derive(Debug, Clone)]
+pub struct Devnet {
+    pub celestia_rpc_url: String,
+    pub range_len: u64,
+    /// The program key of the mock SP1 verifier.
+    pub vkey: B256,
+}
+
+impl Devnet {
+    pub fn sp1_verifier(&self) -> Sp1Verifier {
+        Sp1Verifier::Mock { vkey: self.vkey }
+    }
+
+    /// The block range of the data commitment covering block `height`, and its nonce.
+    pub fn commitment_range(&self, height: u64) -> eyre::Result<(Range<u64>, U256)> {
+        ensure!(self.range_len > 0, "empty data commitment range");
+        ensure!(height > 0, "no data commitment covers celestia height 0");
+        let index = (height - 1) / self.range_len;
+        let start = 1 + index * self.range_len;
+        Ok((start..start + self.range_len, U256::from(index + 1)))
+    }
+
+    /// Computes the data commitment over the data roots of the blocks of `range`.
+    ///
+    /// Fails until the devnet has produced the last block of the range.
+    pub async fn data_commitment(&self, range: Range<u64>) -> eyre::Result<DataCommitment> {
+        let mut data_roots = Vec::new();
+        for height in range.clone() {
+            data_roots.push(get_celestia_block_data_hash(&self.celestia_rpc_url, height).await?);
+        }
+        Ok(DataCommitment::new(range.start, data_roots))
+    }
+
+    /// The data commitment covering block `height`, and the event a relayer would emit for it.
+    pub async fn commit(
+        &self,
+        height: u64,
+    ) -> eyre::Result<(DataCommitment, DataCommitmentStored)> {
+        let (range, nonce) = self.commitment_range(height)?;
+        let commitment = self.data_commitment(range).await?;
+        let event = DataCommitmentStored {
+            proofNonce: nonce,
+            startBlock: commitment.start_block,
+            endBlock: commitment.end_block(),
+            dataCommitment: commitment.root(),
+        };
+        Ok((commitment, event))
+    }
+
+    /// Builds the guest input proving the shares of Celestia tx `tx_hash`, and the event of the data
+    /// commitment attesting it.
+    pub async fn build_guest_input(
+        &self,
+        tx_hash: &[u8],
+    ) -> eyre::Result<(DataCommitmentStored, GuestInput)> {
+        let tx = get_celestia_tx_by_hash(&self.celestia_rpc_url, tx_hash).await?;
+        let (commitment, event) = self.commit(tx.height).await?;
+        let attestation = commitment
+            .attestation_proof(tx.height, event.proofNonce)
+            .ok_or_eyre("celestia height not in the data commitment")?;
+        let public_values = commitment.public_values();
+        let input = GuestInput {
+            commitHeaderRangeProof: sp1_mock_proof(self.vkey, &public_values)?,
+            commitHeaderRangePublicValues: public_values,
+            sharesProof: SharesProof::new(
+                tx.height,
+                attestation.tuple.dataRoot,
+                event.proofNonce,
+                attestation.proof.try_into()?,
+                tx.proof,
+            )?,
+        };
+        Ok((event, input))
+    }
+}
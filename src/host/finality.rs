@@ -0,0 +1,108 @@
+//! Finality of the Ethereum blocks the Blobstream commitments are read from.
+//!
+//! A commitment read from the latest blocks may be reorganized out after the guest input was
+//! built, [`Finality`] bounds the blocks events are searched in, and [`check_commit_tx`] tells
+//! whether a commitment tx used before is still on the canonical chain.
+use crate::{
+    da_oracle::{DataCommitmentStored, SP1BlobstreamInstance, commitHeaderRangeCall},
+    host::{find_commit_tx_with, source::EthereumSource},
+};
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::B256;
+use alloy_provider::Provider;
+use eyre::OptionExt;
+
+/// The Ethereum blocks a `DataCommitmentStored` event may be taken from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Finality {
+    /// Any block up to the head.
+    #[default]
+    Latest,
+    /// Blocks at least this many blocks below the head.
+    Confirmations(u64),
+    /// Blocks up to the finalized checkpoint.
+    Finalized,
+}
+
+impl Finality {
+    /// The last block allowed by this finality.
+    pub async fn last_block(&self, provider: &impl Provider) -> eyre::Result<u64> {
+        match self {
+            Self::Latest => Ok(provider.get_block_number().await?),
+            Self::Confirmations(confirmations) => Ok(provider
+                .get_block_number()
+                .await?
+                .saturating_sub(*confirmations)),
+            Self::Finalized => Ok(provider
+                .get_block_by_number(BlockNumberOrTag::Finalized)
+                .await?
+                .ok_or_eyre("no finalized block")?
+                .header
+                .number),
+        }
+    }
+}
+
+/// A commitment tx which is no longer in the block it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reorged {
+    pub tx_hash: B256,
+    pub block_hash: B256,
+}
+
+impl std::fmt::Display for Reorged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "commit tx {} of block {} was reorged out",
+            self.tx_hash, self.block_hash
+        )
+    }
+}
+
+impl std::error::Error for Reorged {}
+
+/// Checks that commit tx `tx_hash` is still included in block `block_hash`, failing with
+/// [`Reorged`] otherwise.
+pub async fn check_commit_tx(
+    provider: &impl Provider,
+    tx_hash: B256,
+    block_hash: B256,
+) -> eyre::Result<()> {
+    let receipt = provider.get_transaction_receipt(tx_hash).await?;
+    if receipt.and_then(|receipt| receipt.block_hash) != Some(block_hash) {
+        return Err(Reorged {
+            tx_hash,
+            block_hash,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// An SP1 Blobstream contract whose commitments are only taken from blocks of the given finality.
+#[derive(Debug, Clone)]
+pub struct FinalBlobstream<P> {
+    pub contract: SP1BlobstreamInstance<P>,
+    pub finality: Finality,
+}
+
+impl<P: Provider> EthereumSource for FinalBlobstream<P> {
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        find_commit_tx_with(
+            &self.contract,
+            self.contract.provider(),
+            height,
+            self.finality,
+        )
+        .await
+    }
+
+    async fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        self.contract.get_commit_call(tx_hash, event).await
+    }
+}
@@ -0,0 +1,243 @@
+//! Celestia data over the gRPC services of celestia-app, for endpoints whose CometBFT RPC does not
+//! index txs.
+//!
+//! Txs are found with `celestia.core.v1.tx.Tx/TxStatus`, and the proofs of their shares come from
+//! the `custom/txInclusionProof/{index}` ABCI query of celestia-app, which also backs `ProveShares`.
+//! `ProveSharesV2` is not used as it takes the share range of the tx, which the host would only know
+//! by laying out the whole square of the block, while `TxStatus` returns the index of the tx the
+//! query takes.
+use crate::{
+    host::{
+        BlockHeader, GetTx, MerkleProof, RowProof, ShareProof, TxProof, source::CelestiaSource,
+    },
+    native::namespace::NamespaceProof,
+    verifier::Namespace,
+};
+use alloy_primitives::{B256, hex};
+use eyre::{OptionExt, bail, ensure, eyre};
+use prost::Message;
+use proto::{
+    AbciQueryRequest, AbciQueryResponse, DataRootInclusionProofRequest,
+    DataRootInclusionProofResponse, GetBlockByHeightRequest, GetBlockByHeightResponse,
+    MerkleProofMessage, ShareProofMessage, TxStatusRequest, TxStatusResponse,
+};
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+};
+
+pub mod proto;
+
+pub const TX_STATUS: &str = "/celestia.core.v1.tx.Tx/TxStatus";
+pub const ABCI_QUERY: &str = "/cosmos.base.tendermint.v1beta1.Service/ABCIQuery";
+pub const GET_BLOCK_BY_HEIGHT: &str = "/cosmos.base.tendermint.v1beta1.Service/GetBlockByHeight";
+pub const DATA_ROOT_INCLUSION_PROOF: &str =
+    "/tendermint.rpc.grpc.BlobstreamAPI/DataRootInclusionProof";
+
+impl TryFrom<MerkleProofMessage> for MerkleProof {
+    type Error = eyre::Report;
+
+    fn try_from(proof: MerkleProofMessage) -> eyre::Result<Self> {
+        Ok(Self {
+            total: proof.total.try_into()?,
+            index: proof.index.try_into()?,
+            aunts: proof.aunts,
+        })
+    }
+}
+
+impl TryFrom<ShareProofMessage> for TxProof {
+    type Error = eyre::Report;
+
+    fn try_from(proof: ShareProofMessage) -> eyre::Result<Self> {
+        let row_proof = proof
+            .row_proof
+            .ok_or_eyre("share proof without row proof")?;
+        Ok(Self {
+            data: proof.data,
+            share_proofs: proof
+                .share_proofs
+                .into_iter()
+                .map(|proof| {
+                    Ok(ShareProof {
+                        start: proof.start.try_into()?,
+                        end: proof.end.try_into()?,
+                        nodes: proof.nodes,
+                    })
+                })
+                .collect::<eyre::Result<_>>()?,
+            namespace_id: proof.namespace_id,
+            row_proof: RowProof {
+                row_roots: row_proof.row_roots,
+                proofs: row_proof
+                    .proofs
+                    .into_iter()
+                    .map(MerkleProof::try_from)
+                    .collect::<eyre::Result<_>>()?,
+            },
+            namespace_version: proof.namespace_version.try_into()?,
+        })
+    }
+}
+
+/// A celestia-app gRPC endpoint, e.g. `https://celestia-grpc.publicnode.com:443`.
+#[derive(Debug, Clone)]
+pub struct GrpcSource {
+    channel: Channel,
+}
+
+impl GrpcSource {
+    pub async fn connect(url: &str) -> eyre::Result<Self> {
+        let mut endpoint = Endpoint::from_shared(url.to_string())?;
+        if url.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+        }
+        Ok(Self {
+            channel: endpoint.connect().await?,
+        })
+    }
+
+    async fn unary<Req, Res>(&self, path: &'static str, request: Req) -> eyre::Result<Res>
+    where
+        Req: Message + Send + Sync + 'static,
+        Res: Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = Grpc::new(self.channel.clone());
+        grpc.ready().await?;
+        let response = grpc
+            .unary(
+                tonic::Request::new(request),
+                PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// The height and index of a committed tx.
+    pub async fn get_tx_status(&self, tx_hash: B256) -> eyre::Result<(u64, u32)> {
+        let status: TxStatusResponse = self
+            .unary(
+                TX_STATUS,
+                TxStatusRequest {
+                    tx_id: hex::encode_upper(tx_hash),
+                },
+            )
+            .await?;
+        ensure!(
+            status.status == "COMMITTED",
+            "tx {tx_hash} is {}",
+            status.status
+        );
+        ensure!(
+            status.execution_code == 0,
+            "tx {tx_hash} failed: {}",
+            status.error
+        );
+        Ok((status.height.try_into()?, status.index))
+    }
+
+    /// The proof of the shares of the tx at `index` in block `height`.
+    pub async fn get_tx_proof(&self, height: u64, index: u32) -> eyre::Result<TxProof> {
+        let response: AbciQueryResponse = self
+            .unary(
+                ABCI_QUERY,
+                AbciQueryRequest {
+                    data: Vec::new(),
+                    path: format!("custom/txInclusionProof/{index}"),
+                    height: height.try_into()?,
+                    prove: false,
+                },
+            )
+            .await?;
+        if response.code != 0 {
+            bail!("txInclusionProof query failed: {}", response.log);
+        }
+        ShareProofMessage::decode(response.value.as_slice())?.try_into()
+    }
+}
+
+impl CelestiaSource for GrpcSource {
+    async fn get_tx(&self, tx_hash: B256) -> eyre::Result<GetTx> {
+        let (height, index) = self.get_tx_status(tx_hash).await?;
+        let proof = self.get_tx_proof(height, index).await?;
+        Ok(GetTx { height, proof })
+    }
+
+    async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+        Ok(self.get_header(height).await?.data_hash)
+    }
+
+    async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        let response: GetBlockByHeightResponse = self
+            .unary(
+                GET_BLOCK_BY_HEIGHT,
+                GetBlockByHeightRequest {
+                    height: height.try_into()?,
+                },
+            )
+            .await?;
+        let hash = response
+            .block_id
+            .ok_or_else(|| eyre!("block {height} without block id"))?
+            .hash;
+        let header = response
+            .sdk_block
+            .or(response.block)
+            .and_then(|block| block.header)
+            .ok_or_else(|| eyre!("block {height} without header"))?;
+        ensure!(
+            header.height == i64::try_from(height)?,
+            "celestia block {height} returned as block {}",
+            header.height
+        );
+        Ok(BlockHeader {
+            height,
+            hash: B256::try_from(hash.as_slice())
+                .map_err(|_| eyre!("invalid block hash length: {}", hash.len()))?,
+            data_hash: B256::try_from(header.data_hash.as_slice())
+                .map_err(|_| eyre!("invalid data hash length: {}", header.data_hash.len()))?,
+        })
+    }
+
+    async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        let response: DataRootInclusionProofResponse = self
+            .unary(
+                DATA_ROOT_INCLUSION_PROOF,
+                DataRootInclusionProofRequest {
+                    height: height.try_into()?,
+                    start,
+                    end,
+                },
+            )
+            .await?;
+        response
+            .proof
+            .ok_or_eyre("empty data root inclusion proof")?
+            .try_into()
+    }
+
+    async fn get_blob_proof(
+        &self,
+        _height: u64,
+        _namespace: &Namespace,
+        _commitment: &[u8],
+    ) -> eyre::Result<TxProof> {
+        bail!("blobs are not indexed by celestia-app, use a celestia-node source")
+    }
+
+    async fn get_namespace_shares(
+        &self,
+        _height: u64,
+        _namespace: &Namespace,
+    ) -> eyre::Result<NamespaceProof> {
+        bail!("namespaces are not indexed by celestia-app, use a celestia-node source")
+    }
+}
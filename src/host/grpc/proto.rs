@@ -0,0 +1,142 @@
+//! The messages of the celestia-app gRPC services read by [`GrpcSource`](super::GrpcSource), with
+//! only the fields the host reads.
+use prost::Message;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TxStatusRequest {
+    #[prost(string, tag = "1")]
+    pub tx_id: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TxStatusResponse {
+    #[prost(int64, tag = "1")]
+    pub height: i64,
+    #[prost(uint32, tag = "2")]
+    pub index: u32,
+    #[prost(uint32, tag = "3")]
+    pub execution_code: u32,
+    #[prost(string, tag = "4")]
+    pub error: String,
+    #[prost(string, tag = "5")]
+    pub status: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct AbciQueryRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub path: String,
+    #[prost(int64, tag = "3")]
+    pub height: i64,
+    #[prost(bool, tag = "4")]
+    pub prove: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct AbciQueryResponse {
+    #[prost(uint32, tag = "1")]
+    pub code: u32,
+    #[prost(string, tag = "3")]
+    pub log: String,
+    #[prost(bytes = "vec", tag = "7")]
+    pub value: Vec<u8>,
+}
+
+/// `celestia.core.v1.proof.ShareProof`
+#[derive(Clone, PartialEq, Message)]
+pub struct ShareProofMessage {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub data: Vec<Vec<u8>>,
+    #[prost(message, repeated, tag = "2")]
+    pub share_proofs: Vec<NmtProofMessage>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub namespace_id: Vec<u8>,
+    #[prost(message, optional, tag = "4")]
+    pub row_proof: Option<RowProofMessage>,
+    #[prost(uint32, tag = "5")]
+    pub namespace_version: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct NmtProofMessage {
+    #[prost(int32, tag = "1")]
+    pub start: i32,
+    #[prost(int32, tag = "2")]
+    pub end: i32,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub nodes: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct RowProofMessage {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub row_roots: Vec<Vec<u8>>,
+    #[prost(message, repeated, tag = "2")]
+    pub proofs: Vec<MerkleProofMessage>,
+}
+
+/// `tendermint.crypto.Proof`
+#[derive(Clone, PartialEq, Message)]
+pub struct MerkleProofMessage {
+    #[prost(int64, tag = "1")]
+    pub total: i64,
+    #[prost(int64, tag = "2")]
+    pub index: i64,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub aunts: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct GetBlockByHeightRequest {
+    #[prost(int64, tag = "1")]
+    pub height: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct GetBlockByHeightResponse {
+    #[prost(message, optional, tag = "1")]
+    pub block_id: Option<BlockIdMessage>,
+    #[prost(message, optional, tag = "2")]
+    pub block: Option<BlockMessage>,
+    #[prost(message, optional, tag = "3")]
+    pub sdk_block: Option<BlockMessage>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BlockMessage {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<HeaderMessage>,
+}
+
+/// `tendermint.types.BlockID`
+#[derive(Clone, PartialEq, Message)]
+pub struct BlockIdMessage {
+    #[prost(bytes = "vec", tag = "1")]
+    pub hash: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct HeaderMessage {
+    #[prost(int64, tag = "3")]
+    pub height: i64,
+    #[prost(bytes = "vec", tag = "7")]
+    pub data_hash: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct DataRootInclusionProofRequest {
+    #[prost(int64, tag = "1")]
+    pub height: i64,
+    #[prost(uint64, tag = "2")]
+    pub start: u64,
+    #[prost(uint64, tag = "3")]
+    pub end: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct DataRootInclusionProofResponse {
+    #[prost(message, optional, tag = "1")]
+    pub proof: Option<MerkleProofMessage>,
+}
@@ -0,0 +1,245 @@
+//! Local index of the Blobstream commitments, answering which commitment covers a Celestia height
+//! without scanning logs.
+//!
+//! An [`Indexer`] follows the `DataCommitmentStored` events of a contract from a start block, up to
+//! the last block of its [`Finality`] so that indexed events are not reorganized, and persists them
+//! to a file. The commitments cover consecutive ranges, so lookups are binary searches. A
+//! commitment whose tx was reorganized out anyway is dropped on lookup and indexed again.
+use crate::{
+    da_oracle::{DataCommitmentStored, SP1BlobstreamInstance, commitHeaderRangeCall},
+    host::{
+        cache::DEFAULT_REORG_DEPTH,
+        finality::{Finality, Reorged, check_commit_tx},
+        source::EthereumSource,
+    },
+};
+use alloy_primitives::{B256, U256};
+use alloy_provider::Provider;
+use eyre::{OptionExt, eyre};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedCommitment {
+    pub proof_nonce: U256,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub data_commitment: B256,
+    pub eth_tx_hash: B256,
+    pub eth_block_number: u64,
+    pub eth_block_hash: B256,
+}
+
+impl IndexedCommitment {
+    pub fn event(&self) -> DataCommitmentStored {
+        DataCommitmentStored {
+            proofNonce: self.proof_nonce,
+            startBlock: self.start_block,
+            endBlock: self.end_block,
+            dataCommitment: self.data_commitment,
+        }
+    }
+}
+
+/// The indexed commitments, ordered by range.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommitmentIndex {
+    commitments: Vec<IndexedCommitment>,
+    /// The first Ethereum block not indexed yet.
+    pub next_eth_block: u64,
+}
+
+impl CommitmentIndex {
+    pub fn new(start_eth_block: u64) -> Self {
+        Self {
+            commitments: Vec::new(),
+            next_eth_block: start_eth_block,
+        }
+    }
+
+    /// Loads the index at `path`, or starts a new one from `start_eth_block` if there is none.
+    pub fn load_or_new(path: &Path, start_eth_block: u64) -> eyre::Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self::new(start_eth_block))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn commitments(&self) -> &[IndexedCommitment] {
+        &self.commitments
+    }
+
+    /// Adds a commitment, unless one with the same nonce is already indexed. Returns whether it
+    /// was added.
+    pub fn insert(&mut self, commitment: IndexedCommitment) -> bool {
+        if self
+            .commitments
+            .iter()
+            .any(|indexed| indexed.proof_nonce == commitment.proof_nonce)
+        {
+            return false;
+        }
+        let index = self
+            .commitments
+            .partition_point(|indexed| indexed.start_block < commitment.start_block);
+        self.commitments.insert(index, commitment);
+        true
+    }
+
+    /// Drops the commitments of Ethereum blocks from `eth_block` on, which are indexed again by
+    /// the next sync.
+    pub fn rewind(&mut self, eth_block: u64) {
+        self.commitments
+            .retain(|commitment| commitment.eth_block_number < eth_block);
+        self.next_eth_block = self.next_eth_block.min(eth_block);
+    }
+
+    /// The commitment covering Celestia block `height`.
+    pub fn covering(&self, height: u64) -> Option<&IndexedCommitment> {
+        let index = self
+            .commitments
+            .partition_point(|commitment| commitment.start_block <= height);
+        let commitment = self.commitments.get(index.checked_sub(1)?)?;
+        (commitment.end_block > height).then_some(commitment)
+    }
+}
+
+/// Follows the commitments of an SP1 Blobstream contract into a [`CommitmentIndex`].
+#[derive(Debug, Clone)]
+pub struct Indexer<P> {
+    pub contract: SP1BlobstreamInstance<P>,
+    pub path: PathBuf,
+    pub finality: Finality,
+    /// Blocks of logs requested at once.
+    pub batch_size: u64,
+    pub poll_interval: Duration,
+    index: Arc<RwLock<CommitmentIndex>>,
+}
+
+impl<P: Provider> Indexer<P> {
+    /// Opens the index at `path`, indexing from `start_eth_block` if it is new.
+    pub fn open(
+        contract: SP1BlobstreamInstance<P>,
+        path: impl Into<PathBuf>,
+        start_eth_block: u64,
+    ) -> eyre::Result<Self> {
+        let path = path.into();
+        let index = CommitmentIndex::load_or_new(&path, start_eth_block)?;
+        Ok(Self {
+            contract,
+            path,
+            finality: Finality::Confirmations(DEFAULT_REORG_DEPTH),
+            batch_size: 10_000,
+            poll_interval: Duration::from_secs(60),
+            index: Arc::new(RwLock::new(index)),
+        })
+    }
+
+    /// The index, shared with the indexer.
+    pub fn index(&self) -> Arc<RwLock<CommitmentIndex>> {
+        self.index.clone()
+    }
+
+    pub fn covering(&self, height: u64) -> Option<IndexedCommitment> {
+        self.index.read().unwrap().covering(height).cloned()
+    }
+
+    /// Indexes the confirmed blocks not indexed yet, returns the number of new commitments.
+    pub async fn sync(&self) -> eyre::Result<usize> {
+        let last = self.finality.last_block(self.contract.provider()).await?;
+        let mut from = self.index.read().unwrap().next_eth_block;
+        let mut added = 0;
+        while from <= last {
+            let to = last.min(from + self.batch_size - 1);
+            let logs = self
+                .contract
+                .DataCommitmentStored_filter()
+                .from_block(from)
+                .to_block(to)
+                .query()
+                .await?;
+
+            let mut index = self.index.write().unwrap();
+            for (event, log) in logs {
+                let inserted = index.insert(IndexedCommitment {
+                    proof_nonce: event.proofNonce,
+                    start_block: event.startBlock,
+                    end_block: event.endBlock,
+                    data_commitment: event.dataCommitment,
+                    eth_tx_hash: log
+                        .transaction_hash
+                        .ok_or_eyre("DataCommitmentStored log without transaction hash")?,
+                    eth_block_number: log
+                        .block_number
+                        .ok_or_eyre("DataCommitmentStored log without block number")?,
+                    eth_block_hash: log
+                        .block_hash
+                        .ok_or_eyre("DataCommitmentStored log without block hash")?,
+                });
+                added += usize::from(inserted);
+            }
+            index.next_eth_block = to + 1;
+            index.save(&self.path)?;
+            from = to + 1;
+        }
+        Ok(added)
+    }
+
+    /// Syncs every `poll_interval`, to be spawned as a background task.
+    pub async fn run(self) -> eyre::Result<()> {
+        loop {
+            self.sync().await?;
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+impl<P: Provider> EthereumSource for Indexer<P> {
+    /// The indexed commitment covering `height`, once its commit tx is confirmed to still be in its
+    /// block. Otherwise the index is rewound to that block and the [`Reorged`] error returned.
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        let commitment = self
+            .covering(height)
+            .ok_or_else(|| eyre!("no indexed commitment covers celestia height {height}"))?;
+        let checked = check_commit_tx(
+            self.contract.provider(),
+            commitment.eth_tx_hash,
+            commitment.eth_block_hash,
+        )
+        .await;
+        if let Err(err) = checked {
+            if err.is::<Reorged>() {
+                let mut index = self.index.write().unwrap();
+                index.rewind(commitment.eth_block_number);
+                index.save(&self.path)?;
+            }
+            return Err(err);
+        }
+        Ok((commitment.eth_tx_hash, commitment.event()))
+    }
+
+    async fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        self.contract.get_commit_call(tx_hash, event).await
+    }
+}
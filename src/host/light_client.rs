@@ -0,0 +1,204 @@
+//! Light client verification of the Celestia headers the host uses, against a trusted header.
+//!
+//! The height and data hash of the attested block otherwise come from an unverified `/block`
+//! response. Headers are verified with the skipping verification of the Tendermint light client,
+//! bisecting when the validator set changed too much since the trusted header.
+use crate::{
+    GuestInput,
+    host::{RpcResponse, http_client},
+};
+use alloy_primitives::{B256, U256};
+use eyre::{bail, ensure, eyre};
+use serde::Deserialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tendermint::{Time, block::signed_header::SignedHeader, node, serializers, validator};
+use tendermint_light_client_verifier::{
+    ProdVerifier, Verdict, Verifier,
+    options::Options,
+    types::{LightBlock, TrustThreshold},
+};
+
+/// A header trusted out of band, e.g. from a block explorer or a previous verification.
+#[derive(Debug, Clone, Copy)]
+pub struct TrustRoot {
+    pub height: u64,
+    pub hash: B256,
+}
+
+#[derive(Debug, Clone)]
+pub struct LightClientOptions {
+    pub trust_threshold: TrustThreshold,
+    /// Must be shorter than the unbonding period of the chain, 21 days on Celestia.
+    pub trusting_period: Duration,
+    pub clock_drift: Duration,
+}
+
+impl Default for LightClientOptions {
+    fn default() -> Self {
+        Self {
+            trust_threshold: TrustThreshold::ONE_THIRD,
+            trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
+            clock_drift: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A header verified from a [`TrustRoot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedHeader {
+    pub height: u64,
+    pub hash: B256,
+    pub data_hash: B256,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetCommit {
+    signed_header: SignedHeader,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetValidators {
+    validators: Vec<validator::Info>,
+    #[serde(with = "serializers::from_str")]
+    total: usize,
+}
+
+async fn get_signed_header(rpc_url: &str, height: u64) -> eyre::Result<SignedHeader> {
+    let commit = http_client()
+        .get(format!("{rpc_url}/commit?height={height}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<RpcResponse<GetCommit>>()
+        .await?
+        .result;
+    Ok(commit.signed_header)
+}
+
+async fn get_validators(rpc_url: &str, height: u64) -> eyre::Result<validator::Set> {
+    let mut validators = Vec::new();
+    for page in 1.. {
+        let response = http_client()
+            .get(format!(
+                "{rpc_url}/validators?height={height}&page={page}&per_page=100"
+            ))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<RpcResponse<GetValidators>>()
+            .await?
+            .result;
+        let empty = response.validators.is_empty();
+        validators.extend(response.validators);
+        if validators.len() >= response.total || empty {
+            break;
+        }
+    }
+    Ok(validator::Set::without_proposer(validators))
+}
+
+async fn get_light_block(rpc_url: &str, height: u64) -> eyre::Result<LightBlock> {
+    let signed_header = get_signed_header(rpc_url, height).await?;
+    let validators = get_validators(rpc_url, height).await?;
+    let next_validators = get_validators(rpc_url, height + 1).await?;
+    Ok(LightBlock::new(
+        signed_header,
+        validators,
+        next_validators,
+        node::Id::new([0; 20]),
+    ))
+}
+
+fn verified_header(block: &LightBlock) -> eyre::Result<VerifiedHeader> {
+    let header = &block.signed_header.header;
+    let data_hash = header
+        .data_hash
+        .ok_or_else(|| eyre!("header {} without data hash", header.height))?;
+    Ok(VerifiedHeader {
+        height: header.height.value(),
+        hash: B256::from_slice(header.hash().as_bytes()),
+        data_hash: B256::from_slice(data_hash.as_bytes()),
+    })
+}
+
+/// Verifies the header of block `height` from `trust_root`, over the CometBFT RPC at `rpc_url`.
+pub async fn verify_header(
+    rpc_url: &str,
+    trust_root: TrustRoot,
+    height: u64,
+    options: &LightClientOptions,
+) -> eyre::Result<VerifiedHeader> {
+    ensure!(
+        height >= trust_root.height,
+        "celestia height {height} is below the trusted height {}",
+        trust_root.height
+    );
+    let mut trusted = get_light_block(rpc_url, trust_root.height).await?;
+    let trusted_header = verified_header(&trusted)?;
+    ensure!(
+        trusted_header.hash == trust_root.hash,
+        "header {} has hash {}, expected {}",
+        trust_root.height,
+        trusted_header.hash,
+        trust_root.hash
+    );
+    if height == trust_root.height {
+        return Ok(trusted_header);
+    }
+
+    let verifier = ProdVerifier::default();
+    let options = Options {
+        trust_threshold: options.trust_threshold,
+        trusting_period: options.trusting_period,
+        clock_drift: options.clock_drift,
+    };
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let now = Time::from_unix_timestamp(elapsed.as_secs() as i64, elapsed.subsec_nanos())?;
+
+    let mut pivot = height;
+    loop {
+        let untrusted = get_light_block(rpc_url, pivot).await?;
+        match verifier.verify_update_header(
+            untrusted.as_untrusted_state(),
+            trusted.as_trusted_state(),
+            &options,
+            now,
+        ) {
+            Verdict::Success if pivot == height => return verified_header(&untrusted),
+            Verdict::Success => {
+                trusted = untrusted;
+                pivot = height;
+            }
+            Verdict::NotEnoughTrust(_) => {
+                let trusted_height = trusted.signed_header.header.height.value();
+                pivot = trusted_height + (pivot - trusted_height) / 2;
+                // adjacent headers are verified against the next validators of the trusted one
+                ensure!(
+                    pivot > trusted_height,
+                    "bisection stalled at {trusted_height}"
+                );
+            }
+            Verdict::Invalid(detail) => bail!("header {pivot} failed verification: {detail}"),
+        }
+    }
+}
+
+/// Checks that `input` attests the verified header, the data root of a diverging Blobstream
+/// commitment would otherwise only be rejected by the verifier.
+pub fn check_attested_header(input: &GuestInput, header: &VerifiedHeader) -> eyre::Result<()> {
+    let tuple = &input.sharesProof.attestationProof.tuple;
+    ensure!(
+        tuple.height == U256::from(header.height),
+        "input attests celestia height {}, verified header is {}",
+        tuple.height,
+        header.height
+    );
+    ensure!(
+        tuple.dataRoot == header.data_hash,
+        "input attests data root {} of celestia block {}, verified header has {}",
+        tuple.dataRoot,
+        header.height,
+        header.data_hash
+    );
+    Ok(())
+}
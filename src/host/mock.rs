@@ -0,0 +1,455 @@
+//! Local Celestia and Ethereum RPC servers replaying recorded responses, for tests.
+//!
+//! A [`MockRpc`] serves the CometBFT REST paths used by the host (`/tx`, `/block`,
+//! `/data_root_inclusion_proof`) under [`MockRpc::celestia_url`] and the Ethereum JSON-RPC methods
+//! under [`MockRpc::ethereum_url`], from a [`RpcFixture`]. A recording server forwards the requests
+//! it has no response for to real endpoints and adds the responses to its fixture.
+use crate::da_oracle::DataCommitmentStored;
+use alloy_primitives::{Address, B256, Bloom, hex};
+use alloy_sol_types::SolEvent;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{StatusCode, Uri},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::Path as FsPath,
+    sync::{Arc, Mutex},
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// Recorded RPC responses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RpcFixture {
+    #[serde(default)]
+    pub celestia: Vec<CelestiaExchange>,
+    #[serde(default)]
+    pub ethereum: Vec<EthereumExchange>,
+    /// Calls of the JSON-RPC API of celestia-node, recorded as the Ethereum ones.
+    #[serde(default)]
+    pub celestia_node: Vec<EthereumExchange>,
+}
+
+/// A response to a GET request of the Celestia RPC.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CelestiaExchange {
+    /// The path without the leading slash, e.g. `tx`.
+    pub path: String,
+    /// The query parameters the request must have, any request to the path matches if empty.
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    /// The whole response body.
+    pub response: Value,
+}
+
+/// A response to an Ethereum JSON-RPC call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthereumExchange {
+    pub method: String,
+    /// The params the call must have, any call of the method matches if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    /// The `result` of the response.
+    pub result: Value,
+}
+
+impl EthereumExchange {
+    /// Answers any call of `method` with `result`.
+    pub fn new(method: &str, result: Value) -> Self {
+        Self {
+            method: method.to_string(),
+            params: None,
+            result,
+        }
+    }
+
+    /// Only answers the calls of the method with `params`.
+    pub fn with_params(self, params: Value) -> Self {
+        Self {
+            params: Some(params),
+            ..self
+        }
+    }
+}
+
+impl RpcFixture {
+    pub fn load(path: impl AsRef<FsPath>) -> eyre::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<FsPath>) -> eyre::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn celestia_response(
+        &self,
+        path: &str,
+        query: &BTreeMap<String, String>,
+    ) -> Option<Value> {
+        self.celestia
+            .iter()
+            .find(|exchange| {
+                exchange.path == path
+                    && exchange
+                        .query
+                        .iter()
+                        .all(|(key, value)| query.get(key) == Some(value))
+            })
+            .map(|exchange| exchange.response.clone())
+    }
+
+    pub(crate) fn ethereum_result(&self, method: &str, params: &Value) -> Option<Value> {
+        json_rpc_result(&self.ethereum, method, params)
+    }
+
+    pub(crate) fn celestia_node_result(&self, method: &str, params: &Value) -> Option<Value> {
+        json_rpc_result(&self.celestia_node, method, params)
+    }
+}
+
+/// The result of the first of `exchanges` matching a call of `method` with `params`.
+fn json_rpc_result(exchanges: &[EthereumExchange], method: &str, params: &Value) -> Option<Value> {
+    exchanges
+        .iter()
+        .find(|exchange| {
+            exchange.method == method
+                && exchange
+                    .params
+                    .as_ref()
+                    .is_none_or(|expected| expected == params)
+        })
+        .map(|exchange| exchange.result.clone())
+}
+
+/// Endpoints a recording server forwards to.
+#[derive(Debug, Clone)]
+struct Upstream {
+    celestia: String,
+    ethereum: String,
+    celestia_node: Option<String>,
+}
+
+/// The JSON-RPC APIs served next to the Celestia RPC.
+#[derive(Debug, Clone, Copy)]
+enum JsonRpc {
+    Ethereum,
+    CelestiaNode,
+}
+
+impl JsonRpc {
+    fn exchanges(self, fixture: &mut RpcFixture) -> &mut Vec<EthereumExchange> {
+        match self {
+            Self::Ethereum => &mut fixture.ethereum,
+            Self::CelestiaNode => &mut fixture.celestia_node,
+        }
+    }
+
+    fn upstream(self, upstream: &Upstream) -> Option<&str> {
+        match self {
+            Self::Ethereum => Some(&upstream.ethereum),
+            Self::CelestiaNode => upstream.celestia_node.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ServerState {
+    fixture: Mutex<RpcFixture>,
+    upstream: Option<Upstream>,
+    client: reqwest::Client,
+}
+
+type SharedState = Arc<ServerState>;
+
+/// A running mock server, stopped when dropped.
+#[derive(Debug)]
+pub struct MockRpc {
+    address: SocketAddr,
+    state: SharedState,
+    server: JoinHandle<()>,
+}
+
+impl MockRpc {
+    /// Serves the responses of `fixture`, the other requests fail.
+    pub async fn serve(fixture: RpcFixture) -> eyre::Result<Self> {
+        Self::start(fixture, None).await
+    }
+
+    /// Forwards the requests to the given endpoints and records their responses.
+    pub async fn record(celestia_url: &str, ethereum_url: &str) -> eyre::Result<Self> {
+        let upstream = Upstream {
+            celestia: celestia_url.trim_end_matches('/').to_string(),
+            ethereum: ethereum_url.to_string(),
+            celestia_node: None,
+        };
+        Self::start(RpcFixture::default(), Some(upstream)).await
+    }
+
+    /// Same as [`record`](Self::record), also recording the calls to the celestia-node at
+    /// `celestia_node_url`.
+    pub async fn record_with_celestia_node(
+        celestia_url: &str,
+        ethereum_url: &str,
+        celestia_node_url: &str,
+    ) -> eyre::Result<Self> {
+        let upstream = Upstream {
+            celestia: celestia_url.trim_end_matches('/').to_string(),
+            ethereum: ethereum_url.to_string(),
+            celestia_node: Some(celestia_node_url.to_string()),
+        };
+        Self::start(RpcFixture::default(), Some(upstream)).await
+    }
+
+    async fn start(fixture: RpcFixture, upstream: Option<Upstream>) -> eyre::Result<Self> {
+        let state = Arc::new(ServerState {
+            fixture: Mutex::new(fixture),
+            upstream,
+            client: reqwest::Client::new(),
+        });
+        let router = Router::new()
+            .route("/celestia/{*path}", get(celestia))
+            .route("/ethereum", post(ethereum))
+            .route("/celestia-node", post(celestia_node))
+            .with_state(state.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, router).await;
+        });
+        Ok(Self {
+            address,
+            state,
+            server,
+        })
+    }
+
+    /// Base url of the Celestia RPC, to use in place of a CometBFT RPC url.
+    pub fn celestia_url(&self) -> String {
+        format!("http://{}/celestia", self.address)
+    }
+
+    /// Url of the Ethereum JSON-RPC.
+    pub fn ethereum_url(&self) -> String {
+        format!("http://{}/ethereum", self.address)
+    }
+
+    /// Url of the celestia-node JSON-RPC.
+    pub fn celestia_node_url(&self) -> String {
+        format!("http://{}/celestia-node", self.address)
+    }
+
+    /// The served responses, including the recorded ones.
+    pub fn fixture(&self) -> RpcFixture {
+        self.state.fixture.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockRpc {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+type HandlerResult = Result<Json<Value>, (StatusCode, String)>;
+
+async fn celestia(
+    State(state): State<SharedState>,
+    Path(path): Path<String>,
+    Query(query): Query<BTreeMap<String, String>>,
+    uri: Uri,
+) -> HandlerResult {
+    let recorded = state
+        .fixture
+        .lock()
+        .unwrap()
+        .celestia_response(&path, &query);
+    if let Some(response) = recorded {
+        return Ok(Json(response));
+    }
+    let Some(upstream) = &state.upstream else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("no recorded response for {uri}"),
+        ));
+    };
+
+    let url = match uri.query() {
+        Some(raw_query) => format!("{}/{path}?{raw_query}", upstream.celestia),
+        None => format!("{}/{path}", upstream.celestia),
+    };
+    let response = forward(state.client.get(url)).await?;
+    state
+        .fixture
+        .lock()
+        .unwrap()
+        .celestia
+        .push(CelestiaExchange {
+            path,
+            query,
+            response: response.clone(),
+        });
+    Ok(Json(response))
+}
+
+async fn ethereum(State(state): State<SharedState>, Json(request): Json<Value>) -> HandlerResult {
+    json_rpc(&state, JsonRpc::Ethereum, request).await
+}
+
+async fn celestia_node(
+    State(state): State<SharedState>,
+    Json(request): Json<Value>,
+) -> HandlerResult {
+    json_rpc(&state, JsonRpc::CelestiaNode, request).await
+}
+
+async fn json_rpc(state: &ServerState, rpc: JsonRpc, request: Value) -> HandlerResult {
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+    let recorded = json_rpc_result(
+        rpc.exchanges(&mut state.fixture.lock().unwrap()),
+        &method,
+        &params,
+    );
+    if let Some(result) = recorded {
+        return Ok(Json(
+            json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        ));
+    }
+    let Some(upstream) = state
+        .upstream
+        .as_ref()
+        .and_then(|upstream| rpc.upstream(upstream))
+    else {
+        return Ok(Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("no recorded result for {method}") },
+        })));
+    };
+
+    let response = forward(state.client.post(upstream).json(&request)).await?;
+    // errors are passed through without being recorded
+    if let Some(result) = response.get("result") {
+        rpc.exchanges(&mut state.fixture.lock().unwrap())
+            .push(EthereumExchange {
+                method,
+                params: Some(params),
+                result: result.clone(),
+            });
+    }
+    Ok(Json(response))
+}
+
+async fn forward(request: reqwest::RequestBuilder) -> Result<Value, (StatusCode, String)> {
+    let bad_gateway = |e: reqwest::Error| (StatusCode::BAD_GATEWAY, e.to_string());
+    request
+        .send()
+        .await
+        .map_err(bad_gateway)?
+        .json()
+        .await
+        .map_err(bad_gateway)
+}
+
+/// An empty Ethereum block, as returned by `eth_getBlockByNumber`.
+pub fn block(number: u64, hash: B256) -> Value {
+    json!({
+        "hash": hash,
+        "parentHash": B256::repeat_byte(0x01),
+        "sha3Uncles": B256::ZERO,
+        "miner": Address::ZERO,
+        "stateRoot": B256::ZERO,
+        "transactionsRoot": B256::ZERO,
+        "receiptsRoot": B256::ZERO,
+        "logsBloom": Bloom::ZERO,
+        "difficulty": "0x0",
+        "number": format!("{number:#x}"),
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "timestamp": format!("{:#x}", 1_700_000_000 + 12 * number),
+        "extraData": "0x",
+        "mixHash": B256::ZERO,
+        "nonce": "0x0000000000000000",
+        "uncles": [],
+        "transactions": [],
+    })
+}
+
+/// The receipt of a successful tx `tx_hash` of block `number`, without logs.
+pub fn receipt(tx_hash: B256, number: u64, block_hash: B256) -> Value {
+    json!({
+        "type": "0x2",
+        "status": "0x1",
+        "cumulativeGasUsed": "0x10000",
+        "logs": [],
+        "logsBloom": Bloom::ZERO,
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "blockHash": block_hash,
+        "blockNumber": format!("{number:#x}"),
+        "gasUsed": "0x10000",
+        "effectiveGasPrice": "0x1",
+        "from": Address::repeat_byte(0x11),
+        "to": Address::repeat_byte(0x22),
+        "contractAddress": null,
+    })
+}
+
+/// A legacy tx `tx_hash` of block `number` calling `to` with `input`.
+pub fn transaction(
+    tx_hash: B256,
+    number: u64,
+    block_hash: B256,
+    to: Address,
+    input: &[u8],
+) -> Value {
+    json!({
+        "type": "0x0",
+        "chainId": "0x1",
+        "nonce": "0x0",
+        "gasPrice": "0x1",
+        "gas": "0x100000",
+        "to": to,
+        "value": "0x0",
+        "input": hex::encode_prefixed(input),
+        "r": "0x1",
+        "s": "0x1",
+        "v": "0x25",
+        "hash": tx_hash,
+        "blockHash": block_hash,
+        "blockNumber": format!("{number:#x}"),
+        "transactionIndex": "0x0",
+        "from": Address::repeat_byte(0x11),
+    })
+}
+
+/// The log of `event` emitted by `address` in tx `tx_hash` of block `number`.
+pub fn commitment_log(
+    address: Address,
+    event: &DataCommitmentStored,
+    number: u64,
+    block_hash: B256,
+    tx_hash: B256,
+) -> Value {
+    let log = event.encode_log_data();
+    json!({
+        "address": address,
+        "topics": log.topics(),
+        "data": log.data,
+        "blockHash": block_hash,
+        "blockNumber": format!("{number:#x}"),
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false,
+    })
+}
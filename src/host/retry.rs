@@ -0,0 +1,219 @@
+//! Timeouts, retries and failover of the host RPC calls.
+//!
+//! A [`RetryPolicy`] bounds each attempt of a call and retries transient errors with exponential
+//! backoff, a [`Failover`] source moves on to the next endpoint once the retries of one are
+//! exhausted.
+use crate::{
+    da_oracle::{DataCommitmentStored, commitHeaderRangeCall},
+    host::{
+        BlockHeader, GetTx, MerkleProof, TxProof,
+        source::{CelestiaSource, EthereumSource},
+    },
+    native::namespace::NamespaceProof,
+    verifier::Namespace,
+};
+use alloy_primitives::B256;
+use alloy_transport::{RpcError, TransportError};
+use eyre::{bail, eyre};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Timeout of each attempt.
+    pub timeout: Duration,
+    /// Retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each next one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// An attempt that did not finish in time.
+#[derive(Debug)]
+pub struct Timeout(pub Duration);
+
+impl std::fmt::Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "timed out after {:?}", self.0)
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+/// Whether an error is worth retrying: timeouts, connection failures, rate limits and server
+/// errors. Anything else, e.g. a missing tx or a malformed response, fails right away.
+pub fn is_transient(err: &eyre::Report) -> bool {
+    err.chain().any(|cause| {
+        if cause.is::<Timeout>() {
+            return true;
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout()
+                || err.is_connect()
+                || err.status().is_some_and(|status| {
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                });
+        }
+        if let Some(err) = cause.downcast_ref::<TransportError>() {
+            return is_transient_transport(err);
+        }
+        if let Some(alloy_contract::Error::TransportError(err)) =
+            cause.downcast_ref::<alloy_contract::Error>()
+        {
+            return is_transient_transport(err);
+        }
+        #[cfg(feature = "grpc")]
+        if let Some(status) = cause.downcast_ref::<tonic::Status>() {
+            return matches!(
+                status.code(),
+                tonic::Code::Unavailable
+                    | tonic::Code::DeadlineExceeded
+                    | tonic::Code::ResourceExhausted
+            );
+        }
+        #[cfg(feature = "grpc")]
+        if cause.is::<tonic::transport::Error>() {
+            return true;
+        }
+        false
+    })
+}
+
+fn is_transient_transport(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(_) => true,
+        // rate limits, as returned by most providers
+        RpcError::ErrorResp(payload) => matches!(payload.code, 429 | -32005),
+        _ => false,
+    }
+}
+
+impl RetryPolicy {
+    /// Runs `call` until it succeeds, fails with a non transient error or runs out of retries.
+    pub async fn run<T, F, Fut>(&self, mut call: F) -> eyre::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = eyre::Result<T>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            let err = match tokio::time::timeout(self.timeout, call()).await {
+                Ok(Ok(result)) => return Ok(result),
+                Ok(Err(err)) => err,
+                Err(_) => Timeout(self.timeout).into(),
+            };
+            if retries == self.max_retries || !is_transient(&err) {
+                return Err(err);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.max_backoff);
+            retries += 1;
+        }
+    }
+}
+
+/// Sources of the same chain, tried in order.
+#[derive(Debug, Clone)]
+pub struct Failover<S> {
+    pub sources: Vec<S>,
+    pub policy: RetryPolicy,
+}
+
+impl<S> Failover<S> {
+    pub fn new(sources: Vec<S>) -> Self {
+        Self {
+            sources,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Runs `call` on each source in turn, with retries, until one succeeds. Non transient errors
+    /// also move on to the next source, since a broken endpoint may answer with anything.
+    async fn run<'a, T, F, Fut>(&'a self, call: F) -> eyre::Result<T>
+    where
+        F: Fn(&'a S) -> Fut,
+        Fut: Future<Output = eyre::Result<T>>,
+    {
+        let mut errors = Vec::new();
+        for (index, source) in self.sources.iter().enumerate() {
+            match self.policy.run(|| call(source)).await {
+                Ok(result) => return Ok(result),
+                Err(err) => errors.push(format!("source {index}: {err:#}")),
+            }
+        }
+        if errors.is_empty() {
+            bail!("no sources");
+        }
+        Err(eyre!("all sources failed: {}", errors.join("; ")))
+    }
+}
+
+impl<S: CelestiaSource + Sync> CelestiaSource for Failover<S> {
+    async fn get_tx(&self, tx_hash: B256) -> eyre::Result<GetTx> {
+        self.run(|source| source.get_tx(tx_hash)).await
+    }
+
+    async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+        self.run(|source| source.get_block_data_hash(height)).await
+    }
+
+    async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        self.run(|source| source.get_header(height)).await
+    }
+
+    async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        self.run(|source| source.get_data_root_inclusion_proof(height, start, end))
+            .await
+    }
+
+    async fn get_blob_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<TxProof> {
+        self.run(|source| source.get_blob_proof(height, namespace, commitment))
+            .await
+    }
+
+    async fn get_namespace_shares(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> eyre::Result<NamespaceProof> {
+        self.run(|source| source.get_namespace_shares(height, namespace))
+            .await
+    }
+}
+
+impl<S: EthereumSource + Sync> EthereumSource for Failover<S> {
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        self.run(|source| source.find_commit(height)).await
+    }
+
+    async fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        self.run(|source| source.get_commit_call(tx_hash, event))
+            .await
+    }
+}
@@ -0,0 +1,323 @@
+//! Sources of the Celestia and Ethereum data a guest input is built from.
+//!
+//! [`CelestiaSource`] is implemented for the CometBFT RPC, celestia-node, the gRPC services of
+//! celestia-app with the `grpc` feature and recorded fixtures,
+//! [`EthereumSource`] for an SP1 Blobstream contract behind any provider and recorded fixtures.
+use crate::{
+    da_oracle::{DataCommitmentStored, SP1BlobstreamInstance, commitHeaderRangeCall},
+    host::{
+        BlockHeader, GetTx, MerkleProof, TxProof, celestia_node::CelestiaNodeClient,
+        commit_call::find_commit_call, find_commit_tx, get_celestia_block_data_hash,
+        get_celestia_block_header, get_celestia_data_root_inclusion_proof, get_celestia_tx_by_hash,
+    },
+    native::namespace::NamespaceProof,
+    verifier::Namespace,
+};
+use alloy_primitives::B256;
+use alloy_provider::Provider;
+use eyre::bail;
+
+pub trait CelestiaSource {
+    /// A PayForBlobs tx with the proof of its shares.
+    fn get_tx(&self, tx_hash: B256) -> impl Future<Output = eyre::Result<GetTx>> + Send;
+
+    /// The data hash of the header of block `height`.
+    fn get_block_data_hash(&self, height: u64) -> impl Future<Output = eyre::Result<B256>> + Send;
+
+    /// The header of block `height`.
+    fn get_header(&self, height: u64) -> impl Future<Output = eyre::Result<BlockHeader>> + Send;
+
+    /// The proof of the data root tuple of block `height` in the data commitment of the blocks
+    /// `[start, end)`.
+    fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> impl Future<Output = eyre::Result<MerkleProof>> + Send;
+
+    /// The proof of the shares of the blob of `namespace` with the given commitment.
+    fn get_blob_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> impl Future<Output = eyre::Result<TxProof>> + Send;
+
+    /// The proof of all the shares of `namespace` in block `height`, or of their absence.
+    ///
+    /// Only celestia-node serves the shares of a namespace, the other sources fail.
+    fn get_namespace_shares(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> impl Future<Output = eyre::Result<NamespaceProof>> + Send;
+}
+
+pub trait EthereumSource {
+    /// The `DataCommitmentStored` event covering Celestia block `height`, and the hash of its tx.
+    fn find_commit(
+        &self,
+        height: u64,
+    ) -> impl Future<Output = eyre::Result<(B256, DataCommitmentStored)>> + Send;
+
+    /// The `commitHeaderRange` call of tx `tx_hash` which emitted `event`.
+    fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> impl Future<Output = eyre::Result<commitHeaderRangeCall>> + Send;
+}
+
+/// The CometBFT RPC of a celestia-app node.
+#[derive(Debug, Clone)]
+pub struct CometBftSource {
+    pub url: String,
+}
+
+impl CelestiaSource for CometBftSource {
+    async fn get_tx(&self, tx_hash: B256) -> eyre::Result<GetTx> {
+        get_celestia_tx_by_hash(&self.url, tx_hash.as_slice()).await
+    }
+
+    async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+        get_celestia_block_data_hash(&self.url, height).await
+    }
+
+    async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        get_celestia_block_header(&self.url, height).await
+    }
+
+    async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        get_celestia_data_root_inclusion_proof(&self.url, height, start, end).await
+    }
+
+    async fn get_blob_proof(
+        &self,
+        _height: u64,
+        _namespace: &Namespace,
+        _commitment: &[u8],
+    ) -> eyre::Result<TxProof> {
+        bail!("blobs are not indexed by the CometBFT RPC, use a celestia-node source")
+    }
+
+    async fn get_namespace_shares(
+        &self,
+        _height: u64,
+        _namespace: &Namespace,
+    ) -> eyre::Result<NamespaceProof> {
+        bail!("namespaces are not indexed by the CometBFT RPC, use a celestia-node source")
+    }
+}
+
+impl CelestiaSource for CelestiaNodeClient {
+    async fn get_tx(&self, _tx_hash: B256) -> eyre::Result<GetTx> {
+        bail!("celestia-node has no tx lookup, look blobs up by namespace and commitment")
+    }
+
+    async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+        CelestiaNodeClient::get_header(self, height)
+            .await?
+            .data_hash()
+    }
+
+    async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+        CelestiaNodeClient::get_header(self, height)
+            .await?
+            .block_header(height)
+    }
+
+    async fn get_data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> eyre::Result<MerkleProof> {
+        CelestiaNodeClient::get_data_root_inclusion_proof(self, height, start, end).await
+    }
+
+    async fn get_blob_proof(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+        commitment: &[u8],
+    ) -> eyre::Result<TxProof> {
+        let (_, proof) =
+            CelestiaNodeClient::get_blob_proof(self, height, namespace, commitment).await?;
+        Ok(proof)
+    }
+
+    async fn get_namespace_shares(
+        &self,
+        height: u64,
+        namespace: &Namespace,
+    ) -> eyre::Result<NamespaceProof> {
+        let (_, proof) = self.get_namespace_proof(height, namespace).await?;
+        Ok(proof)
+    }
+}
+
+impl<P: Provider> EthereumSource for SP1BlobstreamInstance<P> {
+    async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+        find_commit_tx(self, self.provider(), height).await
+    }
+
+    async fn get_commit_call(
+        &self,
+        tx_hash: B256,
+        event: &DataCommitmentStored,
+    ) -> eyre::Result<commitHeaderRangeCall> {
+        find_commit_call(self.provider(), *self.address(), tx_hash, event).await
+    }
+}
+
+#[cfg(feature = "mock-rpc")]
+mod fixture {
+    use super::*;
+    use crate::host::{
+        GetBlock, GetBlockHeader, GetDataRootInclusionProof, RpcResponse, celestia_node,
+        commit_call::{commits, wrapped_calls},
+        mock::RpcFixture,
+    };
+    use alloy_primitives::{Address, Bytes, hex};
+    use alloy_sol_types::{SolCall, SolEvent};
+    use eyre::OptionExt;
+    use serde::de::DeserializeOwned;
+    use serde_json::{Value, json};
+    use std::collections::BTreeMap;
+
+    fn celestia_result<T: DeserializeOwned>(
+        fixture: &RpcFixture,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> eyre::Result<T> {
+        let query = query
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let response = fixture
+            .celestia_response(path, &query)
+            .ok_or_else(|| eyre::eyre!("no recorded response for /{path} {query:?}"))?;
+        Ok(serde_json::from_value::<RpcResponse<T>>(response)?.result)
+    }
+
+    /// The recorded responses of the CometBFT RPC.
+    impl CelestiaSource for RpcFixture {
+        async fn get_tx(&self, tx_hash: B256) -> eyre::Result<GetTx> {
+            celestia_result(self, "tx", &[("hash", hex::encode_prefixed(tx_hash))])
+        }
+
+        async fn get_block_data_hash(&self, height: u64) -> eyre::Result<B256> {
+            let block: GetBlock =
+                celestia_result(self, "block", &[("height", height.to_string())])?;
+            let data_hash = block.block.header.data_hash;
+            B256::try_from(data_hash.as_slice())
+                .map_err(|_| eyre::eyre!("invalid data hash length: {}", data_hash.len()))
+        }
+
+        async fn get_header(&self, height: u64) -> eyre::Result<BlockHeader> {
+            let block: GetBlockHeader =
+                celestia_result(self, "block", &[("height", height.to_string())])?;
+            block
+                .block
+                .header
+                .block_header(&block.block_id.hash, height)
+        }
+
+        async fn get_data_root_inclusion_proof(
+            &self,
+            height: u64,
+            start: u64,
+            end: u64,
+        ) -> eyre::Result<MerkleProof> {
+            let query = [
+                ("height", height.to_string()),
+                ("start", start.to_string()),
+                ("end", end.to_string()),
+            ];
+            let proof: GetDataRootInclusionProof =
+                celestia_result(self, "data_root_inclusion_proof", &query)?;
+            Ok(proof.proof)
+        }
+
+        async fn get_blob_proof(
+            &self,
+            _height: u64,
+            _namespace: &Namespace,
+            _commitment: &[u8],
+        ) -> eyre::Result<TxProof> {
+            bail!("blobs are not recorded in fixtures")
+        }
+
+        /// The shares are searched in the recorded celestia-node calls.
+        async fn get_namespace_shares(
+            &self,
+            height: u64,
+            namespace: &Namespace,
+        ) -> eyre::Result<NamespaceProof> {
+            let params = celestia_node::namespace_data_params(height, namespace);
+            let rows = self
+                .celestia_node_result("share.GetNamespaceData", &serde_json::to_value(&params)?)
+                .ok_or_eyre("no recorded share.GetNamespaceData call")?;
+            let header = self
+                .celestia_node_result("header.GetByHeight", &json!([height]))
+                .ok_or_eyre("no recorded header.GetByHeight call")?;
+            celestia_node::namespace_proof(
+                namespace,
+                serde_json::from_value(rows)?,
+                serde_json::from_value(header)?,
+            )
+        }
+    }
+
+    /// The recorded responses of the Ethereum JSON-RPC, the logs of every recorded `eth_getLogs`
+    /// are searched whatever their filter.
+    impl EthereumSource for RpcFixture {
+        async fn find_commit(&self, height: u64) -> eyre::Result<(B256, DataCommitmentStored)> {
+            let logs = self
+                .ethereum
+                .iter()
+                .filter(|exchange| exchange.method == "eth_getLogs")
+                .filter_map(|exchange| exchange.result.as_array())
+                .flatten();
+            for log in logs {
+                let topics: Vec<B256> = serde_json::from_value(log["topics"].clone())?;
+                let data: Bytes = serde_json::from_value(log["data"].clone())?;
+                let Ok(event) = DataCommitmentStored::decode_raw_log(topics, &data) else {
+                    continue;
+                };
+                if event.startBlock <= height && event.endBlock > height {
+                    let tx_hash = serde_json::from_value(log["transactionHash"].clone())?;
+                    return Ok((tx_hash, event));
+                }
+            }
+            bail!("no DataCommitmentStored event covering celestia height {height}")
+        }
+
+        /// Only the calldata of the tx is searched, traces are not recorded.
+        async fn get_commit_call(
+            &self,
+            tx_hash: B256,
+            event: &DataCommitmentStored,
+        ) -> eyre::Result<commitHeaderRangeCall> {
+            let tx = self
+                .ethereum_result("eth_getTransactionByHash", &json!([tx_hash]))
+                .filter(|tx: &Value| !tx.is_null())
+                .ok_or_eyre("commit tx not found")?;
+            let to: Address = serde_json::from_value(tx["to"].clone())?;
+            let input: Bytes = serde_json::from_value(tx["input"].clone())?;
+            wrapped_calls(to, &input)
+                .into_iter()
+                .filter_map(|(_, input)| commitHeaderRangeCall::abi_decode(&input).ok())
+                .find(|call| commits(call, event))
+                .ok_or_else(|| {
+                    eyre::eyre!("no commitHeaderRange call committing {event:?} in {tx_hash}")
+                })
+        }
+    }
+}
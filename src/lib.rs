@@ -4,8 +4,12 @@ pub mod verifier;
 pub mod guest;
 #[cfg(feature = "host")]
 pub mod host;
+pub mod native;
+pub mod profile;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod synthetic;
 
-#[cfg(feature = "openvm")]
+#[cfg(any(feature = "openvm", feature = "openvm-host"))]
 pub mod openvm;
 
 // re-export in case revm version is different
@@ -0,0 +1,214 @@
+//! Rust port of the [`Verifier`](crate::verifier::Verifier) contract.
+//!
+//! The merkle proofs are checked natively, the SP1 proof is still checked by the verifier bytecode
+//! as the Plonk verifier has no Rust counterpart yet.
+use crate::{
+    guest::{self, Reverted, Sp1Verifier},
+    profile::{Stage, StageInspector},
+    verifier::{AttestationProof, InvalidProof, SharesProof, verifyCall, verifyProofCall},
+};
+use alloy_primitives::{B256, Bytes, keccak256};
+use alloy_sol_types::{SolCall, SolError, SolValue, sol};
+use eyre::{Report, bail, ensure};
+
+pub mod binary_merkle;
+pub mod namespace;
+pub mod nmt;
+pub mod shares;
+
+sol! {
+    /// Public values of the SP1 Blobstream `commitHeaderRange` program.
+    #[derive(Debug)]
+    struct ProofOutputs {
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        bytes32 dataCommitment;
+        uint64 trustedBlock;
+        uint64 targetBlock;
+        uint256 validatorBitmap;
+    }
+}
+
+/// Maximum number of blocks covered by a data commitment, `DATA_COMMITMENT_MAX` of the contract.
+pub const DATA_COMMITMENT_MAX: u64 = 10_000;
+
+/// Same as `Verifier.verify`, returns the commit of the input.
+///
+/// The errors carry the revert reasons of the contract where there is one.
+pub fn verify(input: &verifyCall) -> eyre::Result<B256> {
+    verify_with(input, Sp1Verifier::default())
+}
+
+/// Same as [`verify`], with the SP1 proof checked by `sp1`.
+pub fn verify_with(input: &verifyCall, sp1: Sp1Verifier) -> eyre::Result<B256> {
+    verify_stages(input, Some(sp1), None)
+}
+
+/// Same as [`verify`] without checking the SP1 proof, the other checks of the verifier still run.
+pub fn verify_skipping_sp1(input: &verifyCall) -> eyre::Result<B256> {
+    verify_stages(input, None, None)
+}
+
+/// Same as [`verify_with`], entering each [`Stage`] of the verification on `stages`.
+///
+/// The verification stops with the error of [`StageInspector::enter`], if any.
+pub fn verify_staged(
+    input: &verifyCall,
+    sp1: Sp1Verifier,
+    stages: &mut StageInspector,
+) -> eyre::Result<B256> {
+    verify_stages(input, Some(sp1), Some(stages))
+}
+
+/// Runs the checks of the verifier, the SP1 proof is skipped if `sp1` is not set.
+fn verify_stages(
+    input: &verifyCall,
+    sp1: Option<Sp1Verifier>,
+    mut stages: Option<&mut StageInspector>,
+) -> eyre::Result<B256> {
+    // `abi.decode` reverts without data
+    let outputs = ProofOutputs::abi_decode_validate(&input.commitHeaderRangePublicValues)
+        .map_err(|e| Report::new(Reverted::from_output(&[])).wrap_err(e))?;
+    let Some(range) = outputs.targetBlock.checked_sub(outputs.trustedBlock) else {
+        return Err(Report::new(Reverted::arithmetic())
+            .wrap_err("arithmetic underflow in the proof block range"));
+    };
+    ensure!(range <= DATA_COMMITMENT_MAX, "proof block range too large");
+
+    let attestation = &input.sharesProof.attestationProof;
+    // the contract truncates the height with `uint64(height)`
+    let height = attestation.tuple.height.as_limbs()[0];
+    ensure!(
+        outputs.trustedBlock <= height && height <= outputs.targetBlock,
+        "attested block not in range"
+    );
+
+    enter(&mut stages, Stage::Sp1Proof)?;
+    if let Some(sp1) = sp1 {
+        verify_sp1_proof(
+            &input.commitHeaderRangePublicValues,
+            &input.commitHeaderRangeProof,
+            sp1,
+            stages.as_deref_mut(),
+        )?;
+    }
+
+    enter(&mut stages, Stage::Attestation)?;
+    ensure!(
+        verify_attestation(&outputs.dataCommitment, attestation),
+        "invalid BinaryMerkleTree proof"
+    );
+
+    enter(&mut stages, Stage::RowProofs)?;
+    let shares_proof = &input.sharesProof;
+    ensure!(
+        verify_rows(shares_proof, &attestation.tuple.dataRoot),
+        "invalid sharesProof"
+    );
+    enter(&mut stages, Stage::NmtProofs)?;
+    ensure!(verify_share_proofs(shares_proof)?, "invalid sharesProof");
+
+    enter(&mut stages, Stage::CommitHashing)?;
+    Ok(keccak256(&input.abi_encode()[4..]))
+}
+
+fn enter(stages: &mut Option<&mut StageInspector>, stage: Stage) -> eyre::Result<()> {
+    stages
+        .as_deref_mut()
+        .map_or(Ok(()), |stages| stages.enter(stage))
+}
+
+/// Verifies the SP1 `commitHeaderRange` proof of `public_values` against the program key of the
+/// verifier, as [`verify`] does.
+pub fn verify_commit_header_range(
+    public_values: &Bytes,
+    proof: &Bytes,
+    sp1: Sp1Verifier,
+) -> eyre::Result<()> {
+    verify_sp1_proof(public_values, proof, sp1, None)
+}
+
+/// Verifies the SP1 proof of the public values against the program key of the verifier, by
+/// running its `verifyProof`, under the inspector `stages` if set.
+fn verify_sp1_proof(
+    public_values: &Bytes,
+    proof: &Bytes,
+    sp1: Sp1Verifier,
+    stages: Option<&mut StageInspector>,
+) -> eyre::Result<()> {
+    let call = verifyProofCall {
+        programVKey: guest::program_vkey(sp1)?,
+        publicValues: public_values.clone(),
+        proofBytes: proof.clone(),
+    };
+    let result = match stages {
+        Some(stages) => guest::inspect_verifier(call.abi_encode(), sp1, stages),
+        None => guest::call_verifier(call.abi_encode(), sp1),
+    };
+    let invalid_proof = Reverted::from_output(&InvalidProof {}.abi_encode());
+    match result {
+        Ok(_) => Ok(()),
+        // `verifyProof` reverts where `Verifier.verify` requires the `Verify` call to succeed
+        Err(e) if e.downcast_ref() == Some(&invalid_proof) => {
+            bail!("invalid commitHeaderRange proof")
+        }
+        Err(e) => Err(e.wrap_err("invalid commitHeaderRange proof")),
+    }
+}
+
+/// Same as `Verifier.verifyAttestation` with the tuple root `root`.
+fn verify_attestation(root: &B256, attestation: &AttestationProof) -> bool {
+    binary_merkle::verify(root, &attestation.proof, &attestation.tuple.abi_encode())
+}
+
+/// First half of `DAVerifier.verifySharesToDataRootTupleRoot` once the attestation is verified:
+/// the row roots are in the data root.
+fn verify_rows(proof: &SharesProof, data_root: &B256) -> bool {
+    proof.rowRoots.len() == proof.rowProofs.len()
+        && proof
+            .rowRoots
+            .iter()
+            .zip(&proof.rowProofs)
+            .all(|(root, row_proof)| binary_merkle::verify(data_root, row_proof, &root.to_bytes()))
+}
+
+/// Second half of `DAVerifier.verifySharesToDataRootTupleRoot`: the shares are in the rows.
+///
+/// A range ending before it begins, or ending at 0, underflows in the contract.
+fn verify_share_proofs(proof: &SharesProof) -> eyre::Result<bool> {
+    if proof.shareProofs.len() != proof.rowRoots.len() {
+        return Ok(false);
+    }
+    let mut lens = Vec::with_capacity(proof.shareProofs.len());
+    for share_proof in &proof.shareProofs {
+        let Some(len) = share_proof.endKey.checked_sub(share_proof.beginKey) else {
+            return Err(Report::new(Reverted::arithmetic())
+                .wrap_err("arithmetic underflow in the share proof range"));
+        };
+        let Ok(len) = usize::try_from(len) else {
+            return Ok(false);
+        };
+        lens.push(len);
+    }
+    if lens
+        .iter()
+        .try_fold(0usize, |sum, len| sum.checked_add(*len))
+        != Some(proof.data.len())
+    {
+        return Ok(false);
+    }
+
+    let mut cursor = 0;
+    for ((share_proof, root), len) in proof.shareProofs.iter().zip(&proof.rowRoots).zip(lens) {
+        if share_proof.endKey.is_zero() {
+            return Err(Report::new(Reverted::arithmetic())
+                .wrap_err("arithmetic underflow in an empty share proof"));
+        }
+        let shares = &proof.data[cursor..cursor + len];
+        cursor += len;
+        if !nmt::verify_multi(root, share_proof, &proof.namespace, shares) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
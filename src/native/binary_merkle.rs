@@ -0,0 +1,133 @@
+//! Port of `BinaryMerkleTree` from the blobstream contracts, an RFC-6962 style merkle tree.
+use crate::verifier::BinaryMerkleProof;
+use alloy_primitives::{B256, U256};
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub fn leaf_digest(data: &[u8]) -> B256 {
+    B256::from_slice(
+        &Sha256::new()
+            .chain_update([LEAF_PREFIX])
+            .chain_update(data)
+            .finalize(),
+    )
+}
+
+pub fn node_digest(left: &B256, right: &B256) -> B256 {
+    B256::from_slice(
+        &Sha256::new()
+            .chain_update([NODE_PREFIX])
+            .chain_update(left)
+            .chain_update(right)
+            .finalize(),
+    )
+}
+
+/// Verifies that `data` is the leaf at `proof.key` of the tree with `root`.
+pub fn verify(root: &B256, proof: &BinaryMerkleProof, data: &[u8]) -> bool {
+    let one = U256::from(1);
+    if proof.numLeaves <= one {
+        if !proof.sideNodes.is_empty() {
+            return false;
+        }
+    } else if U256::from(proof.sideNodes.len()) != path_length_from_key(proof.key, proof.numLeaves)
+    {
+        return false;
+    }
+    if proof.key >= proof.numLeaves {
+        return false;
+    }
+
+    let leaf = leaf_digest(data);
+    if proof.sideNodes.is_empty() {
+        return proof.numLeaves == one && leaf == *root;
+    }
+    compute_root(proof.key, proof.numLeaves, leaf, &proof.sideNodes).as_ref() == Some(root)
+}
+
+/// Computes the root of a tree of `num_leaves` leaves, given the leaf at `key` and its side nodes,
+/// from the bottom up. Returns `None` if the number of side nodes does not match the tree.
+pub fn compute_root(key: U256, num_leaves: U256, leaf: B256, side_nodes: &[B256]) -> Option<B256> {
+    if num_leaves.is_zero() {
+        return None;
+    }
+    if num_leaves == U256::from(1) {
+        return side_nodes.is_empty().then_some(leaf);
+    }
+    let (sibling, side_nodes) = side_nodes.split_last()?;
+    let num_left = split_point(num_leaves);
+    if key < num_left {
+        let left = compute_root(key, num_left, leaf, side_nodes)?;
+        Some(node_digest(&left, sibling))
+    } else {
+        let right = compute_root(key - num_left, num_leaves - num_left, leaf, side_nodes)?;
+        Some(node_digest(sibling, &right))
+    }
+}
+
+/// Computes the root of the tree with the given leaves, as celestia does for its data root.
+pub fn compute_root_from_leaves<T: AsRef<[u8]>>(leaves: &[T]) -> B256 {
+    match leaves {
+        [] => B256::from_slice(&Sha256::digest([])),
+        [leaf] => leaf_digest(leaf.as_ref()),
+        _ => {
+            let k = split_point(U256::from(leaves.len())).to::<usize>();
+            let left = compute_root_from_leaves(&leaves[..k]);
+            let right = compute_root_from_leaves(&leaves[k..]);
+            node_digest(&left, &right)
+        }
+    }
+}
+
+/// Builds the proof of the leaf at `key` of the tree with the given leaves.
+pub fn prove<T: AsRef<[u8]>>(leaves: &[T], key: usize) -> BinaryMerkleProof {
+    fn side_nodes<T: AsRef<[u8]>>(leaves: &[T], key: usize, nodes: &mut Vec<B256>) {
+        if leaves.len() <= 1 {
+            return;
+        }
+        let k = split_point(U256::from(leaves.len())).to::<usize>();
+        if key < k {
+            side_nodes(&leaves[..k], key, nodes);
+            nodes.push(compute_root_from_leaves(&leaves[k..]));
+        } else {
+            side_nodes(&leaves[k..], key - k, nodes);
+            nodes.push(compute_root_from_leaves(&leaves[..k]));
+        }
+    }
+
+    let mut nodes = Vec::new();
+    side_nodes(leaves, key, &mut nodes);
+    BinaryMerkleProof {
+        sideNodes: nodes,
+        key: U256::from(key),
+        numLeaves: U256::from(leaves.len()),
+    }
+}
+
+/// Number of side nodes of the proof of the leaf at `key`.
+fn path_length_from_key(key: U256, num_leaves: U256) -> U256 {
+    let one = U256::from(1);
+    if num_leaves <= one {
+        return U256::ZERO;
+    }
+    // height of the left subtree
+    let height = (num_leaves - one).bit_len();
+    let num_left = one << (height - 1);
+    if key < num_left {
+        U256::from(height)
+    } else if num_left == one || num_leaves - num_left <= one {
+        one
+    } else {
+        one + path_length_from_key(key - num_left, num_leaves - num_left)
+    }
+}
+
+/// The largest power of two strictly less than `x`, or 0 if `x` is 1.
+///
+/// Note: `x` must not be zero.
+pub(crate) fn split_point(x: U256) -> U256 {
+    let k = U256::from(1) << (x.bit_len() - 1);
+    if k == x { k >> 1 } else { k }
+}
@@ -0,0 +1,177 @@
+//! Proofs that a set of shares is all the data of a namespace in a block, or that the block has
+//! none.
+//!
+//! The shares of a block are sorted by namespace across the rows of the data square, so the rows
+//! holding a namespace are consecutive and the min and max namespaces of the row roots tell which
+//! rows may hold it. A [`NamespaceProof`] covers these rows along with the rows right before and
+//! after them, which must end below and start above the namespace, unless the square ends there.
+//! Within each row the proven range must hold every leaf of the namespace, see
+//! [`nmt::verify_namespace_range`]. Where a row may hold the namespace but does not, the leaf
+//! following its position is proven instead, as celestia-node does for absence proofs.
+use super::{binary_merkle, nmt};
+use crate::verifier::{BinaryMerkleProof, Namespace, NamespaceMerkleMultiproof, NamespaceNode};
+use alloy_primitives::{B256, Bytes, U256, keccak256};
+use alloy_sol_types::{SolValue, sol};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+sol! {
+    /// What a verified [`NamespaceProof`] attests, the openvm program built with `namespace`
+    /// reveals the keccak of its ABI encoding.
+    #[derive(Debug, PartialEq, Eq)]
+    struct NamespaceOutputs {
+        /// The commit of the guest input, as returned by the verifier.
+        bytes32 inputCommit;
+        bytes29 namespace;
+        bytes32 dataRoot;
+        /// The keccak of the shares of the namespace, concatenated in order.
+        bytes32 sharesDigest;
+    }
+}
+
+impl NamespaceOutputs {
+    /// The keccak of the ABI encoding, as revealed by the openvm program.
+    pub fn digest(&self) -> B256 {
+        keccak256(self.abi_encode())
+    }
+}
+
+/// A row of the data square, with the shares of the namespace it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceRow {
+    pub root: NamespaceNode,
+    /// Proof of the row root in the data root.
+    pub root_proof: BinaryMerkleProof,
+    /// Proof of the shares in the row, unused for rows outside of the namespace range.
+    pub proof: NamespaceMerkleMultiproof,
+    pub shares: Vec<Bytes>,
+    /// The first leaf above the namespace, proven instead of shares when the row has none.
+    pub absence_leaf: Option<NamespaceNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceProof {
+    pub namespace: Namespace,
+    /// Consecutive rows, from the one before the namespace to the one after it.
+    pub rows: Vec<NamespaceRow>,
+}
+
+impl NamespaceProof {
+    /// The shares of the namespace, in order.
+    pub fn shares(&self) -> impl Iterator<Item = &Bytes> {
+        self.rows.iter().flat_map(|row| &row.shares)
+    }
+
+    /// The keccak of the shares of the namespace, concatenated in order.
+    pub fn shares_digest(&self) -> B256 {
+        keccak256(
+            self.shares()
+                .flat_map(|share| share.iter().copied())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// Whether the namespace range of `root` includes `namespace`.
+pub fn covers(root: &NamespaceNode, namespace: &Namespace) -> bool {
+    let namespace = namespace.to_bytes();
+    root.min.to_bytes() <= namespace && namespace <= root.max.to_bytes()
+}
+
+/// The rows of the square with `row_roots` which may hold `namespace`, and the rows a
+/// [`NamespaceProof`] of it covers.
+pub fn namespace_rows(
+    row_roots: &[NamespaceNode],
+    namespace: &Namespace,
+) -> (Range<usize>, Range<usize>) {
+    let covering = match row_roots.iter().position(|root| covers(root, namespace)) {
+        Some(start) => {
+            let len = row_roots[start..]
+                .iter()
+                .take_while(|root| covers(root, namespace))
+                .count();
+            start..start + len
+        }
+        None => {
+            let namespace = namespace.to_bytes();
+            let above = row_roots
+                .iter()
+                .position(|root| root.min.to_bytes() > namespace)
+                .unwrap_or(row_roots.len());
+            above..above
+        }
+    };
+    let proven = covering.start.saturating_sub(1)..(covering.end + 1).min(row_roots.len());
+    (covering, proven)
+}
+
+fn verify_row(row: &NamespaceRow, namespace: &Namespace) -> bool {
+    if !covers(&row.root, namespace) {
+        return row.shares.is_empty() && row.absence_leaf.is_none();
+    }
+    if !row.shares.is_empty() {
+        let leaves = row
+            .shares
+            .iter()
+            .map(|share| nmt::leaf_digest(namespace, share))
+            .collect::<Vec<_>>();
+        return nmt::verify_namespace_range(&row.root, &row.proof, namespace, &leaves);
+    }
+    let Some(leaf) = &row.absence_leaf else {
+        return false;
+    };
+    leaf.min.to_bytes() == leaf.max.to_bytes()
+        && leaf.min.to_bytes() > namespace.to_bytes()
+        && nmt::verify_namespace_range(&row.root, &row.proof, namespace, std::slice::from_ref(leaf))
+}
+
+/// Verifies that `proof` holds all the shares of its namespace in the block with `data_root`.
+pub fn verify(proof: &NamespaceProof, data_root: &B256) -> bool {
+    let (Some(first), Some(last)) = (proof.rows.first(), proof.rows.last()) else {
+        return false;
+    };
+    if proof.namespace.to_bytes() == Namespace::PARITY_SHARE.to_bytes() {
+        return false;
+    }
+
+    // the data root commits to the row roots then the column roots of the square, as many. The
+    // number of leaves is a power of two, so that no proof of a low row verifies with a smaller
+    // count, which would end the square early.
+    let leaves = first.root_proof.numLeaves;
+    if leaves < U256::from(2) || !leaves.is_power_of_two() {
+        return false;
+    }
+    let width = leaves / U256::from(2);
+    let first_row = first.root_proof.key;
+    let consecutive = proof.rows.iter().enumerate().all(|(index, row)| {
+        row.root_proof.numLeaves == leaves && row.root_proof.key == first_row + U256::from(index)
+    });
+    if !consecutive || last.root_proof.key >= width {
+        return false;
+    }
+    // the rows are as wide as the square
+    if proof.rows.iter().any(|row| row.proof.endKey > width) {
+        return false;
+    }
+    let rows_included = proof
+        .rows
+        .iter()
+        .all(|row| binary_merkle::verify(data_root, &row.root_proof, &row.root.to_bytes()));
+    if !rows_included {
+        return false;
+    }
+
+    // no row before nor after may hold the namespace
+    let namespace = proof.namespace.to_bytes();
+    if !(first_row.is_zero() || first.root.max.to_bytes() < namespace) {
+        return false;
+    }
+    if !(last.root_proof.key + U256::from(1) == width || last.root.min.to_bytes() > namespace) {
+        return false;
+    }
+
+    proof
+        .rows
+        .iter()
+        .all(|row| verify_row(row, &proof.namespace))
+}
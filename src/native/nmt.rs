@@ -0,0 +1,234 @@
+//! Port of `NamespaceMerkleTree` from the blobstream contracts.
+use super::binary_merkle::split_point;
+use crate::verifier::{Namespace, NamespaceMerkleMultiproof, NamespaceNode};
+use alloy_primitives::{B256, Bytes, FixedBytes, U256};
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub fn leaf_digest(namespace: &Namespace, data: &[u8]) -> NamespaceNode {
+    let digest = Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(namespace.to_bytes())
+        .chain_update(data)
+        .finalize();
+    NamespaceNode {
+        min: namespace.clone(),
+        max: namespace.clone(),
+        digest: B256::from_slice(&digest),
+    }
+}
+
+/// Hashes two children, ignoring the parity namespace in the max namespace of the parent.
+pub fn node_digest(left: &NamespaceNode, right: &NamespaceNode) -> NamespaceNode {
+    let parity = Namespace::PARITY_SHARE.to_bytes();
+    let max = if left.min.to_bytes() == parity {
+        Namespace::PARITY_SHARE
+    } else if right.min.to_bytes() == parity || left.max.to_bytes() >= right.max.to_bytes() {
+        left.max.clone()
+    } else {
+        right.max.clone()
+    };
+    let digest = Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left.to_bytes())
+        .chain_update(right.to_bytes())
+        .finalize();
+    NamespaceNode {
+        min: left.min.clone(),
+        max,
+        digest: B256::from_slice(&digest),
+    }
+}
+
+/// Computes the root of the tree with the given leaf nodes.
+pub fn compute_root(leaves: &[NamespaceNode]) -> NamespaceNode {
+    match leaves {
+        [] => NamespaceNode {
+            digest: B256::from_slice(&Sha256::digest([])),
+            ..nil_node()
+        },
+        [leaf] => leaf.clone(),
+        _ => {
+            let k = split_point(U256::from(leaves.len())).to::<usize>();
+            node_digest(&compute_root(&leaves[..k]), &compute_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Builds the proof of the leaves in `[begin, end)` of the tree with the given leaf nodes.
+///
+/// The side nodes are the roots of the largest subtrees outside of the range, from left to right,
+/// which is the order the contract consumes them in for trees of a power of two leaves.
+pub fn prove(leaves: &[NamespaceNode], begin: usize, end: usize) -> NamespaceMerkleMultiproof {
+    fn side_nodes(
+        leaves: &[NamespaceNode],
+        offset: usize,
+        range: (usize, usize),
+        nodes: &mut Vec<NamespaceNode>,
+    ) {
+        let (begin, end) = range;
+        if offset + leaves.len() <= begin || offset >= end {
+            nodes.push(compute_root(leaves));
+            return;
+        }
+        if leaves.len() <= 1 {
+            return;
+        }
+        let k = split_point(U256::from(leaves.len())).to::<usize>();
+        side_nodes(&leaves[..k], offset, range, nodes);
+        side_nodes(&leaves[k..], offset + k, range, nodes);
+    }
+
+    let mut nodes = Vec::new();
+    side_nodes(leaves, 0, (begin, end), &mut nodes);
+    NamespaceMerkleMultiproof {
+        beginKey: U256::from(begin),
+        endKey: U256::from(end),
+        sideNodes: nodes,
+    }
+}
+
+/// Verifies that `data` are the leaves in `[proof.beginKey, proof.endKey)` of the tree with
+/// `root`, all in `namespace`.
+pub fn verify_multi(
+    root: &NamespaceNode,
+    proof: &NamespaceMerkleMultiproof,
+    namespace: &Namespace,
+    data: &[Bytes],
+) -> bool {
+    let leaves = data
+        .iter()
+        .map(|data| leaf_digest(namespace, data))
+        .collect::<Vec<_>>();
+    verify_multi_hashes(root, proof, &leaves)
+}
+
+/// Verifies that `leaves` are the leaf nodes in `[proof.beginKey, proof.endKey)` of the tree with
+/// `root`.
+pub fn verify_multi_hashes(
+    root: &NamespaceNode,
+    proof: &NamespaceMerkleMultiproof,
+    leaves: &[NamespaceNode],
+) -> bool {
+    left_side_nodes(root, proof, leaves).is_some()
+}
+
+/// Same as [`verify_multi_hashes`], also checking that the range holds every leaf of `namespace`
+/// in the tree: the side nodes on its left end below `namespace` and those on its right start
+/// above it.
+pub fn verify_namespace_range(
+    root: &NamespaceNode,
+    proof: &NamespaceMerkleMultiproof,
+    namespace: &Namespace,
+    leaves: &[NamespaceNode],
+) -> bool {
+    if proof.endKey.checked_sub(proof.beginKey) != Some(U256::from(leaves.len())) {
+        return false;
+    }
+    let Some(left) = left_side_nodes(root, proof, leaves) else {
+        return false;
+    };
+    let namespace = namespace.to_bytes();
+    let (before, after) = proof.sideNodes.split_at(left);
+    before.iter().all(|node| node.max.to_bytes() < namespace)
+        && after.iter().all(|node| node.min.to_bytes() > namespace)
+}
+
+/// Verifies the proof as [`verify_multi_hashes`], returns the number of side nodes on the left of
+/// the range if it holds.
+fn left_side_nodes(
+    root: &NamespaceNode,
+    proof: &NamespaceMerkleMultiproof,
+    leaves: &[NamespaceNode],
+) -> Option<usize> {
+    // the contract reverts on an empty range
+    if proof.endKey.is_zero() {
+        return None;
+    }
+    // estimate the size of the subtree containing the proof range
+    let estimate = split_point(proof.endKey).checked_mul(U256::from(2))?;
+    let estimate = estimate.max(U256::from(1));
+
+    let mut cursor = Cursor {
+        proof,
+        leaves,
+        proof_head: 0,
+        leaves_head: 0,
+        left_side_nodes: 0,
+    };
+    let (mut computed, _) = cursor.compute_root(U256::ZERO, estimate);
+    for side_node in &proof.sideNodes[cursor.proof_head.min(proof.sideNodes.len())..] {
+        computed = node_digest(&computed, side_node);
+    }
+    (computed.to_bytes() == root.to_bytes()).then_some(cursor.left_side_nodes)
+}
+
+/// State of the `_computeRoot` recursion of the contract: the next side node and leaf to use.
+struct Cursor<'a> {
+    proof: &'a NamespaceMerkleMultiproof,
+    leaves: &'a [NamespaceNode],
+    proof_head: usize,
+    leaves_head: usize,
+    /// Side nodes popped for subtrees on the left of the proof range.
+    left_side_nodes: usize,
+}
+
+impl Cursor<'_> {
+    /// Computes the root of the subtree over `[begin, end)`, returns whether it is nil, i.e. the
+    /// subtree does not exist.
+    fn compute_root(&mut self, begin: U256, end: U256) -> (NamespaceNode, bool) {
+        let in_range = |key: U256| self.proof.beginKey <= key && key < self.proof.endKey;
+
+        if end - begin == U256::from(1) {
+            if in_range(begin) {
+                return pop(self.leaves, &mut self.leaves_head);
+            }
+            return self.pop_side_node(begin < self.proof.beginKey);
+        }
+        if end <= self.proof.beginKey || begin >= self.proof.endKey {
+            return self.pop_side_node(end <= self.proof.beginKey);
+        }
+
+        let k = split_point(end - begin);
+        let (left, _) = self.compute_root(begin, begin + k);
+        let (right, right_is_nil) = self.compute_root(begin + k, end);
+        // only the right subtree can be missing
+        if right_is_nil {
+            return (left, false);
+        }
+        (node_digest(&left, &right), false)
+    }
+
+    fn pop_side_node(&mut self, left_of_range: bool) -> (NamespaceNode, bool) {
+        let (node, is_nil) = pop(&self.proof.sideNodes, &mut self.proof_head);
+        if left_of_range && !is_nil {
+            self.left_side_nodes += 1;
+        }
+        (node, is_nil)
+    }
+}
+
+/// Pops the node at `head`, or a zeroed nil node if there is none left.
+fn pop(nodes: &[NamespaceNode], head: &mut usize) -> (NamespaceNode, bool) {
+    match nodes.get(*head) {
+        Some(node) => {
+            *head += 1;
+            (node.clone(), false)
+        }
+        None => (nil_node(), true),
+    }
+}
+
+fn nil_node() -> NamespaceNode {
+    let namespace = Namespace {
+        version: FixedBytes::ZERO,
+        id: FixedBytes::ZERO,
+    };
+    NamespaceNode {
+        min: namespace.clone(),
+        max: namespace,
+        digest: B256::ZERO,
+    }
+}
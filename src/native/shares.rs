@@ -0,0 +1,48 @@
+//! Splitting of blobs into Celestia shares, as celestia-app lays them out in the square.
+use crate::verifier::Namespace;
+use alloy_primitives::FixedBytes;
+
+pub const SHARE_SIZE: usize = 512;
+pub const NAMESPACE_SIZE: usize = 29;
+/// Size of the namespace and of the info byte.
+const SHARE_PREFIX_SIZE: usize = NAMESPACE_SIZE + 1;
+/// Size of the sequence length of the first share of a blob.
+const SEQUENCE_LEN_SIZE: usize = 4;
+
+/// The namespace of the shares padding the square after the last blob, a secondary reserved
+/// namespace of version `0xff` just below [`Namespace::PARITY_SHARE`].
+pub const TAIL_PADDING_NAMESPACE: Namespace = Namespace {
+    version: FixedBytes([0xff]),
+    id: FixedBytes([
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    ]),
+};
+
+#[derive(Debug, Clone)]
+pub struct Blob {
+    pub namespace: Namespace,
+    pub data: Vec<u8>,
+}
+
+impl Blob {
+    /// Splits the blob into shares, as a sparse share sequence of share version 0.
+    pub fn to_shares(&self) -> Vec<Vec<u8>> {
+        let namespace = self.namespace.to_bytes();
+        let first_len = SHARE_SIZE - SHARE_PREFIX_SIZE - SEQUENCE_LEN_SIZE;
+        let (first, rest) = self.data.split_at(first_len.min(self.data.len()));
+
+        let mut shares = Vec::new();
+        let mut share = [namespace.as_slice(), &[0x01]].concat();
+        share.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+        share.extend_from_slice(first);
+        shares.push(share);
+        for chunk in rest.chunks(SHARE_SIZE - SHARE_PREFIX_SIZE) {
+            shares.push([namespace.as_slice(), &[0x00], chunk].concat());
+        }
+        for share in &mut shares {
+            share.resize(SHARE_SIZE, 0);
+        }
+        shares
+    }
+}
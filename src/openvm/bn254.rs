@@ -1,9 +1,15 @@
 // Copied from https://github.com/axiom-crypto/revm/blob/v75-openvm/crates/precompile/src/bn128.rs under MIT License
-use revm::precompile::PrecompileError;
+//
+// Error semantics follow the default (arkworks) backend of revm:
+// - inputs shorter than expected are right padded with zeroes, longer inputs are truncated,
+//   the same way the EVM precompile entry points do before calling into [`crate::RevmCrypto`].
+// - pairing inputs must be exactly one G1 and one G2 point per pair.
+// - G2 points must be in the r-torsion subgroup.
+use revm::precompile::{PrecompileError, utilities::right_pad};
 use std::vec::Vec;
 
 use openvm_ecc_guest::{
-    AffinePoint,
+    AffinePoint, Group,
     algebra::IntMod,
     weierstrass::{IntrinsicCurve, WeierstrassPoint},
 };
@@ -34,6 +40,19 @@ const FQ2_LEN: usize = 2 * FQ_LEN;
 /// Note: A G1 element contains 2 Fq elements.
 const G1_LEN: usize = 2 * FQ_LEN;
 
+/// G2_LEN specifies the number of bytes needed to represent a G2 element.
+///
+/// Note: A G2 element contains 2 Fq^2 elements.
+const G2_LEN: usize = 2 * FQ2_LEN;
+
+/// The order of the BN254 scalar field, big-endian.
+///
+/// Note: G1 has cofactor 1, so only G2 points need a subgroup check against it.
+const SCALAR_MODULUS: [u8; SCALAR_LEN] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
 #[inline]
 fn read_fq(input: &[u8]) -> Result<Fp, PrecompileError> {
     if input.len() < FQ_LEN {
@@ -50,11 +69,9 @@ fn read_fq(input: &[u8]) -> Result<Fp, PrecompileError> {
 /// The second component is parsed before the first, ie if a we represent an
 /// element in Fq2 as (x,y) -- `y` is parsed before `x`
 ///
-/// # Panics
-///
-/// Panics if the input is not at least 64 bytes long.
+/// Note: The input must be exactly [`FQ2_LEN`] bytes long, which is guaranteed by the callers.
 #[inline]
-fn read_fq2(input: &[u8]) -> Result<Fp2, PrecompileError> {
+fn read_fq2(input: &[u8; FQ2_LEN]) -> Result<Fp2, PrecompileError> {
     let y = read_fq(&input[..FQ_LEN])?;
     let x = read_fq(&input[FQ_LEN..2 * FQ_LEN])?;
     Ok(Fp2::new(x, y))
@@ -70,11 +87,10 @@ fn new_g1_affine_point(px: Fp, py: Fp) -> Result<G1Affine, PrecompileError> {
 /// Parses a G1 point from a byte slice by reading two consecutive field elements
 /// representing the x and y coordinates.
 ///
-/// # Panics
-///
-/// Panics if the input is not at least 64 bytes long.
+/// Note: Inputs shorter than [`G1_LEN`] are right padded with zeroes, longer inputs are truncated.
 #[inline]
 pub(super) fn read_g1_point(input: &[u8]) -> Result<G1Affine, PrecompileError> {
+    let input = right_pad::<G1_LEN>(input);
     let px = read_fq(&input[0..FQ_LEN])?;
     let py = read_fq(&input[FQ_LEN..2 * FQ_LEN])?;
     new_g1_affine_point(px, py)
@@ -106,33 +122,49 @@ pub(super) fn encode_g1_point(point: G1Affine) -> [u8; G1_LEN] {
 /// Parses a G2 point from a byte slice by reading four consecutive Fq field elements
 /// representing the two Fq2 coordinates (x and y) of the G2 point.
 ///
-/// # Panics
-///
-/// Panics if the input is not at least 128 bytes long.
+/// Note: Inputs shorter than [`G2_LEN`] are right padded with zeroes, longer inputs are truncated.
+/// Points outside of the r-torsion subgroup are rejected.
 #[inline]
 pub(super) fn read_g2_point(input: &[u8]) -> Result<G2Affine, PrecompileError> {
-    let ba = read_fq2(&input[0..FQ2_LEN])?;
-    let bb = read_fq2(&input[FQ2_LEN..2 * FQ2_LEN])?;
+    let input = right_pad::<G2_LEN>(input);
+    let ba = read_fq2(input[0..FQ2_LEN].try_into().unwrap())?;
+    let bb = read_fq2(input[FQ2_LEN..2 * FQ2_LEN].try_into().unwrap())?;
+
+    let point = G2Affine::from_xy(ba, bb).ok_or(PrecompileError::Bn254AffineGFailedToCreate)?;
+    if !is_in_g2_subgroup(&point) {
+        return Err(PrecompileError::Bn254AffineGFailedToCreate);
+    }
+    Ok(point)
+}
 
-    G2Affine::from_xy(ba, bb).ok_or(PrecompileError::Bn254AffineGFailedToCreate)
+/// Checks that a G2 point, assumed to be on the curve, lies in the r-torsion subgroup.
+///
+/// Note: This is a plain double-and-add of `[r]P`, the point at infinity is always accepted.
+#[inline]
+fn is_in_g2_subgroup(point: &G2Affine) -> bool {
+    if Group::is_identity(point) {
+        return true;
+    }
+    let mut acc = <G2Affine as Group>::IDENTITY;
+    for byte in SCALAR_MODULUS {
+        for bit in (0..8).rev() {
+            Group::double_assign(&mut acc);
+            if (byte >> bit) & 1 == 1 {
+                acc += point;
+            }
+        }
+    }
+    Group::is_identity(&acc)
 }
 
 /// Reads a scalar from the input slice
 ///
 /// Note: The scalar does not need to be canonical.
-///
-/// # Panics
-///
-/// If `input.len()` is not equal to [`SCALAR_LEN`].
+/// Inputs shorter than [`SCALAR_LEN`] are right padded with zeroes, longer inputs are truncated.
 #[inline]
 pub(super) fn read_scalar(input: &[u8]) -> Scalar {
-    assert_eq!(
-        input.len(),
-        SCALAR_LEN,
-        "unexpected scalar length. got {}, expected {SCALAR_LEN}",
-        input.len()
-    );
-    Scalar::from_be_bytes_unchecked(input)
+    let input = right_pad::<SCALAR_LEN>(input);
+    Scalar::from_be_bytes_unchecked(input.as_slice())
 }
 
 /// Performs point addition on two G1 points.
@@ -152,23 +184,25 @@ pub(super) fn g1_point_mul(p: G1Affine, fr: Scalar) -> G1Affine {
 ///
 /// Note: If the input is empty, this function returns true.
 /// This is different to EIP2537 which disallows the empty input.
+///
+/// Every pair is fully validated, even if one of its points is at infinity.
 #[inline]
 pub(super) fn pairing_check(pairs: &[(&[u8], &[u8])]) -> Result<bool, PrecompileError> {
     let mut g1_points = Vec::with_capacity(pairs.len());
     let mut g2_points = Vec::with_capacity(pairs.len());
 
     for (g1_bytes, g2_bytes) in pairs {
-        let g1_is_zero = g1_bytes.iter().all(|i| *i == 0);
-        let g2_is_zero = g2_bytes.iter().all(|i| *i == 0);
+        if g1_bytes.len() != G1_LEN || g2_bytes.len() != G2_LEN {
+            return Err(PrecompileError::Bn254PairLength);
+        }
 
         let g1 = read_g1_point(g1_bytes)?;
         let g2 = read_g2_point(g2_bytes)?;
 
-        let (g1x, g1y) = g1.into_coords();
-        let (g2x, g2y) = g2.into_coords();
-
         // Skip pairs where either point is at infinity
-        if !g1_is_zero && !g2_is_zero {
+        if !Group::is_identity(&g1) && !Group::is_identity(&g2) {
+            let (g1x, g1y) = g1.into_coords();
+            let (g2x, g2y) = g2.into_coords();
             let g1 = AffinePoint::new(g1x, g1y);
             let g2 = AffinePoint::new(g2x, g2y);
             g1_points.push(g1);
@@ -0,0 +1,244 @@
+use crate::{
+    guest::VERIFIER_ADDRESS,
+    verifier::{verifyAttestationCall, verifyCall},
+};
+use alloy_primitives::{Address, Bytes};
+use alloy_sol_types::SolCall;
+use eyre::eyre;
+use revm::{
+    Inspector,
+    bytecode::opcode,
+    context::ContextTr,
+    interpreter::{
+        CallInputs, CallOutcome, Gas, InstructionResult, Interpreter, InterpreterResult,
+        interpreter_types::Jumps,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Stages of the verification, in execution order.
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Stage {
+    /// Reading the input, setting up the EVM and decoding the public values.
+    #[default]
+    Setup,
+    /// Hashing the public values and verifying the SP1 `commitHeaderRange` proof.
+    Sp1Proof,
+    /// Inclusion of the data root tuple in the Blobstream data commitment.
+    Attestation,
+    /// Inclusion of the row roots in the data root.
+    RowProofs,
+    /// Inclusion of the shares in the rows.
+    NmtProofs,
+    /// Hashing the calldata into the commit.
+    CommitHashing,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::Setup,
+        Stage::Sp1Proof,
+        Stage::Attestation,
+        Stage::RowProofs,
+        Stage::NmtProofs,
+        Stage::CommitHashing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Setup => "setup",
+            Stage::Sp1Proof => "sp1_proof",
+            Stage::Attestation => "attestation",
+            Stage::RowProofs => "row_proofs",
+            Stage::NmtProofs => "nmt_proofs",
+            Stage::CommitHashing => "commit_hashing",
+        }
+    }
+
+    /// The stage following this one, if any.
+    pub fn next(&self) -> Option<Stage> {
+        Stage::ALL.get(*self as usize + 1).copied()
+    }
+}
+
+/// What a [`StageInspector`] observed during an execution.
+#[derive(Debug, Clone, Default)]
+pub struct StageReport {
+    /// The last stage entered.
+    pub reached: Stage,
+    /// Number of precompile calls, per stage and precompile address.
+    pub precompile_calls: BTreeMap<(Stage, Address), u64>,
+}
+
+/// Tracks the verification stage, marked by the native verifier or inferred from the EVM execution
+/// of the verifier bytecode.
+///
+/// The native verifier enters each stage explicitly with [`StageInspector::enter`], see
+/// [`native::verify_staged`](crate::native::verify_staged). As the inspector of an EVM execution of
+/// `verify`, it enters the stages at their first call: the `sha256` of the public values, the
+/// `verifyAttestation` call of the verifier to itself, the return of that call, the first `sha256`
+/// of a namespace node or leaf, and the `KECCAK256` of the commit. In both cases it counts the
+/// precompile calls of the EVM executions under the current stage.
+///
+/// If `stop_before` is set, the verification fails when that stage is entered, so the cost of the
+/// stages can be measured as the difference of consecutive runs.
+#[derive(Debug, Default)]
+pub struct StageInspector {
+    stop_before: Option<Stage>,
+    report: StageReport,
+    /// Whether the stages are inferred from the inspected execution of `verify`.
+    tracking: bool,
+    /// Number of call frames entered and not returned yet.
+    depth: usize,
+    /// Whether the inspected execution was stopped.
+    stopped: bool,
+}
+
+/// Name of the precompile at `address`, if it is one of the precompiles used by the verifier.
+pub fn precompile_name(address: Address) -> Option<&'static str> {
+    const NAMES: [&str; 10] = [
+        "ecrecover",
+        "sha256",
+        "ripemd160",
+        "identity",
+        "modexp",
+        "bn254_add",
+        "bn254_mul",
+        "bn254_pairing",
+        "blake2f",
+        "point_evaluation",
+    ];
+    let (prefix, index) = address.split_at(19);
+    if prefix.iter().any(|b| *b != 0) || index[0] == 0 {
+        return None;
+    }
+    NAMES.get(index[0] as usize - 1).copied()
+}
+
+impl StageInspector {
+    pub fn new(stop_before: Option<Stage>) -> Self {
+        Self {
+            stop_before,
+            ..Default::default()
+        }
+    }
+
+    pub fn into_report(self) -> StageReport {
+        self.report
+    }
+
+    /// Enters `stage`, fails if the verification must stop before it.
+    pub fn enter(&mut self, stage: Stage) -> eyre::Result<()> {
+        if self.stop_before.is_some_and(|stop| stage >= stop) {
+            return Err(eyre!("stopped before stage {}", stage.name()));
+        }
+        self.report.reached = stage;
+        Ok(())
+    }
+
+    /// The stage the inspected execution was stopped before, if it was.
+    pub fn stopped(&self) -> Option<Stage> {
+        self.stop_before.filter(|_| self.stopped)
+    }
+
+    /// Enters `stage` in the inspected execution, returns whether it must stop.
+    fn track(&mut self, stage: Stage) -> bool {
+        if stage > self.report.reached {
+            self.stopped |= self.enter(stage).is_err();
+        }
+        self.stopped
+    }
+
+    /// The stage entered by the inspected call `inputs` of `verify`, if any.
+    fn stage_of(&self, inputs: &CallInputs, input: &[u8]) -> Option<Stage> {
+        let reached = self.report.reached;
+        if inputs.target_address == VERIFIER_ADDRESS {
+            return input
+                .starts_with(&verifyAttestationCall::SELECTOR)
+                .then_some(Stage::Attestation);
+        }
+        // only the calls made by `verify` itself, the SP1 verifier makes its own ones
+        if self.depth != 2 || inputs.bytecode_address != SHA256 {
+            return None;
+        }
+        match reached {
+            Stage::Setup => Some(Stage::Sp1Proof),
+            // the binary merkle nodes and leaves of the row roots hash 65 and 91 bytes, the
+            // namespace ones more
+            Stage::RowProofs if input.len() > 91 => Some(Stage::NmtProofs),
+            _ => None,
+        }
+    }
+}
+
+/// Address of the `sha256` precompile.
+const SHA256: Address = Address::with_last_byte(2);
+
+/// An outcome reverting the call `inputs`.
+fn revert(inputs: &CallInputs) -> CallOutcome {
+    CallOutcome::new(
+        InterpreterResult::new(
+            InstructionResult::Revert,
+            Bytes::new(),
+            Gas::new(inputs.gas_limit),
+        ),
+        inputs.return_memory_offset.clone(),
+    )
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for StageInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        if !self.tracking {
+            return;
+        }
+        let hashing_commit = self.depth == 1
+            && self.report.reached >= Stage::Attestation
+            && interp.bytecode.opcode() == opcode::KECCAK256;
+        if self.stopped || (hashing_commit && self.track(Stage::CommitHashing)) {
+            interp.halt(InstructionResult::Revert);
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.depth += 1;
+        if self.depth == 1 {
+            let input = inputs.input.bytes(context);
+            self.tracking = input.starts_with(&verifyCall::SELECTOR);
+            if self.tracking {
+                self.report.reached = Stage::Setup;
+            }
+        } else if self.tracking {
+            let input = inputs.input.bytes(context);
+            let stage = self.stage_of(inputs, &input);
+            if stage.is_some_and(|stage| self.track(stage)) {
+                return Some(revert(inputs));
+            }
+        }
+        if precompile_name(inputs.bytecode_address).is_some() {
+            *self
+                .report
+                .precompile_calls
+                .entry((self.report.reached, inputs.bytecode_address))
+                .or_default() += 1;
+        }
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.depth -= 1;
+        let attested = self.tracking
+            && self.report.reached == Stage::Attestation
+            && inputs.target_address == VERIFIER_ADDRESS
+            && outcome.result.is_ok()
+            && inputs
+                .input
+                .bytes(context)
+                .starts_with(&verifyAttestationCall::SELECTOR);
+        if attested && self.track(Stage::RowProofs) {
+            *outcome = revert(inputs);
+        }
+    }
+}
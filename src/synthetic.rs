@@ -0,0 +1,291 @@
+//! Synthetic Celestia blocks and Blobstream data commitments, for tests.
+//!
+//! The parity shares of the extended squares are pseudo-random instead of erasure coded, the
+//! verifier only checks the merkle proofs and does not see the difference.
+use crate::{
+    guest::{self, Sp1Verifier},
+    native::{
+        ProofOutputs, binary_merkle,
+        namespace::{self, NamespaceProof, NamespaceRow},
+        nmt,
+        shares::{Blob, NAMESPACE_SIZE, SHARE_SIZE, TAIL_PADDING_NAMESPACE},
+    },
+    verifier::{
+        AttestationProof, DataRootTuple, Namespace, NamespaceMerkleMultiproof, NamespaceNode,
+        SharesProof,
+    },
+};
+use alloy_primitives::{B256, Bytes, U256};
+use alloy_sol_types::SolValue;
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+/// An extended data square of width `2 * width`, with the blobs in the original square.
+#[derive(Debug, Clone)]
+pub struct ExtendedSquare {
+    /// Width of the original square.
+    pub width: usize,
+    /// Shares of the original square, in row-major order.
+    pub shares: Vec<Vec<u8>>,
+    pub row_roots: Vec<NamespaceNode>,
+    pub column_roots: Vec<NamespaceNode>,
+    /// Share ranges of the blobs, in the order they were given.
+    pub blobs: Vec<Range<usize>>,
+}
+
+impl ExtendedSquare {
+    /// Lays out the blobs in namespace order in the smallest square holding them, and extends it.
+    pub fn new(blobs: &[Blob]) -> Self {
+        let mut order = (0..blobs.len()).collect::<Vec<_>>();
+        order.sort_by_key(|i| blobs[*i].namespace.to_bytes());
+
+        let mut shares = Vec::new();
+        let mut ranges = vec![0..0; blobs.len()];
+        for i in order {
+            let blob_shares = blobs[i].to_shares();
+            ranges[i] = shares.len()..shares.len() + blob_shares.len();
+            shares.extend(blob_shares);
+        }
+        let width = (shares.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let width = width.next_power_of_two();
+        let padding = Blob {
+            namespace: TAIL_PADDING_NAMESPACE,
+            data: Vec::new(),
+        }
+        .to_shares()
+        .remove(0);
+        shares.resize(width * width, padding);
+
+        let mut square = Self {
+            width,
+            shares,
+            row_roots: Vec::new(),
+            column_roots: Vec::new(),
+            blobs: ranges,
+        };
+        square.row_roots = (0..2 * width)
+            .map(|row| nmt::compute_root(&square.row_leaves(row)))
+            .collect();
+        square.column_roots = (0..2 * width)
+            .map(|column| {
+                let leaves = (0..2 * width)
+                    .map(|row| square.leaf(row, column))
+                    .collect::<Vec<_>>();
+                nmt::compute_root(&leaves)
+            })
+            .collect();
+        square
+    }
+
+    /// The leaf node of the share at `row` and `column` of the extended square.
+    fn leaf(&self, row: usize, column: usize) -> NamespaceNode {
+        if row < self.width && column < self.width {
+            let share = &self.shares[row * self.width + column];
+            let namespace = Namespace::try_from(&share[..NAMESPACE_SIZE]).unwrap();
+            nmt::leaf_digest(&namespace, share)
+        } else {
+            nmt::leaf_digest(&Namespace::PARITY_SHARE, &parity_share(row, column))
+        }
+    }
+
+    /// The leaf nodes of the extended row `row`.
+    fn row_leaves(&self, row: usize) -> Vec<NamespaceNode> {
+        (0..2 * self.width)
+            .map(|column| self.leaf(row, column))
+            .collect()
+    }
+
+    /// Leaves of the data root: the row roots followed by the column roots.
+    fn data_root_leaves(&self) -> Vec<[u8; 90]> {
+        self.row_roots
+            .iter()
+            .chain(&self.column_roots)
+            .map(NamespaceNode::to_bytes)
+            .collect()
+    }
+
+    pub fn data_root(&self) -> B256 {
+        binary_merkle::compute_root_from_leaves(&self.data_root_leaves())
+    }
+
+    /// The proof of the shares of the blob at `index`, with the given attestation of the data root.
+    pub fn shares_proof(&self, index: usize, attestation: AttestationProof) -> SharesProof {
+        let range = self.blobs[index].clone();
+        let width = self.width;
+        let data_root_leaves = self.data_root_leaves();
+
+        let mut shares_proof = SharesProof {
+            data: self.shares[range.clone()]
+                .iter()
+                .map(|share| Bytes::from(share.clone()))
+                .collect(),
+            shareProofs: Vec::new(),
+            namespace: Namespace::try_from(&self.shares[range.start][..NAMESPACE_SIZE]).unwrap(),
+            rowRoots: Vec::new(),
+            rowProofs: Vec::new(),
+            attestationProof: attestation,
+        };
+        for row in range.start / width..range.end.div_ceil(width) {
+            let begin = range.start.max(row * width) - row * width;
+            let end = range.end.min((row + 1) * width) - row * width;
+            shares_proof
+                .shareProofs
+                .push(nmt::prove(&self.row_leaves(row), begin, end));
+            shares_proof.rowRoots.push(self.row_roots[row].clone());
+            shares_proof
+                .rowProofs
+                .push(binary_merkle::prove(&data_root_leaves, row));
+        }
+        shares_proof
+    }
+
+    /// The proof of all the shares of `namespace` in the square, or of their absence.
+    pub fn namespace_proof(&self, namespace: &Namespace) -> NamespaceProof {
+        let data_root_leaves = self.data_root_leaves();
+        let (covering, proven) = namespace::namespace_rows(&self.row_roots, namespace);
+        let rows = proven
+            .map(|row| {
+                let mut proven_row = NamespaceRow {
+                    root: self.row_roots[row].clone(),
+                    root_proof: binary_merkle::prove(&data_root_leaves, row),
+                    proof: NamespaceMerkleMultiproof {
+                        beginKey: U256::ZERO,
+                        endKey: U256::ZERO,
+                        sideNodes: Vec::new(),
+                    },
+                    shares: Vec::new(),
+                    absence_leaf: None,
+                };
+                if !covering.contains(&row) {
+                    return proven_row;
+                }
+                let leaves = self.row_leaves(row);
+                let namespace = namespace.to_bytes();
+                let begin = leaves
+                    .iter()
+                    .position(|leaf| leaf.min.to_bytes() >= namespace)
+                    .unwrap_or(leaves.len());
+                let end = leaves
+                    .iter()
+                    .position(|leaf| leaf.min.to_bytes() > namespace)
+                    .unwrap_or(leaves.len());
+                if begin < end {
+                    proven_row.proof = nmt::prove(&leaves, begin, end);
+                    proven_row.shares = self.shares
+                        [row * self.width + begin..row * self.width + end]
+                        .iter()
+                        .map(|share| Bytes::from(share.clone()))
+                        .collect();
+                } else {
+                    proven_row.proof = nmt::prove(&leaves, begin, begin + 1);
+                    proven_row.absence_leaf = Some(leaves[begin].clone());
+                }
+                proven_row
+            })
+            .collect();
+        NamespaceProof {
+            namespace: namespace.clone(),
+            rows,
+        }
+    }
+}
+
+/// Pseudo-random share of the extended part of the square.
+fn parity_share(row: usize, column: usize) -> Vec<u8> {
+    (0..SHARE_SIZE / 32)
+        .flat_map(|i| {
+            Sha256::new()
+                .chain_update(b"parity")
+                .chain_update((row as u64).to_be_bytes())
+                .chain_update((column as u64).to_be_bytes())
+                .chain_update((i as u64).to_be_bytes())
+                .finalize()
+        })
+        .collect()
+}
+
+/// The data root tuples of the consecutive blocks `[start_block, start_block + data_roots.len())`,
+/// committed to by a Blobstream data commitment.
+#[derive(Debug, Clone)]
+pub struct DataCommitment {
+    pub start_block: u64,
+    pub tuples: Vec<DataRootTuple>,
+}
+
+impl DataCommitment {
+    pub fn new(start_block: u64, data_roots: impl IntoIterator<Item = B256>) -> Self {
+        let tuples = data_roots
+            .into_iter()
+            .zip(start_block..)
+            .map(|(data_root, height)| DataRootTuple {
+                height: U256::from(height),
+                dataRoot: data_root,
+            })
+            .collect();
+        Self {
+            start_block,
+            tuples,
+        }
+    }
+
+    /// The first block after the range.
+    pub fn end_block(&self) -> u64 {
+        self.start_block + self.tuples.len() as u64
+    }
+
+    fn leaves(&self) -> Vec<Vec<u8>> {
+        self.tuples.iter().map(SolValue::abi_encode).collect()
+    }
+
+    pub fn root(&self) -> B256 {
+        binary_merkle::compute_root_from_leaves(&self.leaves())
+    }
+
+    /// The proof of the tuple of block `height`, if it is in the range.
+    pub fn attestation_proof(&self, height: u64, nonce: U256) -> Option<AttestationProof> {
+        let index = height.checked_sub(self.start_block)? as usize;
+        let tuple = self.tuples.get(index)?.clone();
+        Some(AttestationProof {
+            tupleRootNonce: nonce,
+            tuple,
+            proof: binary_merkle::prove(&self.leaves(), index),
+        })
+    }
+
+    /// Public values of a `commitHeaderRange` proof of the range.
+    pub fn public_values(&self) -> Bytes {
+        ProofOutputs {
+            trustedHeaderHash: B256::ZERO,
+            targetHeaderHash: B256::ZERO,
+            dataCommitment: self.root(),
+            trustedBlock: self.start_block,
+            targetBlock: self.end_block(),
+            validatorBitmap: U256::ZERO,
+        }
+        .abi_encode()
+        .into()
+    }
+}
+
+/// A proof for the stub SP1 verifier: the selector of the verifier and no proof data.
+pub fn sp1_stub_proof() -> eyre::Result<Bytes> {
+    let verifier_hash = guest::verifier_hash(Sp1Verifier::Stub)?;
+    Ok(Bytes::copy_from_slice(&verifier_hash[..4]))
+}
+
+/// An SP1 proof of `public_values` for the program key `vkey`, which only
+/// [`Sp1Verifier::Mock`] with the same key accepts.
+#[cfg(feature = "test-verifier")]
+pub fn sp1_mock_proof(vkey: B256, public_values: &Bytes) -> eyre::Result<Bytes> {
+    use crate::verifier::hashPublicValuesCall;
+    use alloy_sol_types::SolCall;
+
+    let call = hashPublicValuesCall {
+        publicValues: public_values.clone(),
+    };
+    let output = guest::call_verifier(call.abi_encode(), Sp1Verifier::Plonk)?;
+    let digest = hashPublicValuesCall::abi_decode_returns(&output)?;
+    Ok([&sp1_stub_proof()?[..], &(vkey, digest).abi_encode()]
+        .concat()
+        .into())
+}
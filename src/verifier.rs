@@ -1,44 +1,78 @@
 pub use Verifier::*;
-use alloy_primitives::B256;
+use alloy_primitives::{B256, FixedBytes, b256};
 use alloy_sol_types::sol;
+use eyre::eyre;
 use serde::{Deserialize, Serialize};
 
 sol!(
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
     Verifier,
     "src/Verifier.json"
 );
 
+/// `SP1_BLOB_STREAM_PROGRAM_VK` of [`DEPLOYED_BYTECODE`].
+pub const SP1_BLOB_STREAM_PROGRAM_VKEY: B256 =
+    b256!("0x00de39c136b88dfeacb832629e21a9667935bc0e74aaa21292e4f237d79d0bef");
+
 impl Namespace {
-    pub fn new(version: u8, id: &[u8]) -> Self {
-        let id: [u8; 28] = id.try_into().expect("invalid namespace id length");
-        Self {
+    pub fn new(version: u8, id: &[u8]) -> eyre::Result<Self> {
+        let id: [u8; 28] = id
+            .try_into()
+            .map_err(|_| eyre!("invalid namespace id length: {}", id.len()))?;
+        Ok(Self {
             version: version.into(),
             id: id.into(),
-        }
+        })
+    }
+
+    /// The namespace of the parity shares, the maximum namespace.
+    pub const PARITY_SHARE: Self = Self {
+        version: FixedBytes([0xff]),
+        id: FixedBytes([0xff; 28]),
+    };
+
+    /// Serializes the namespace as `version || id`, which orders the same way as the namespace.
+    pub fn to_bytes(&self) -> [u8; 29] {
+        let mut bytes = [0u8; 29];
+        bytes[0] = self.version[0];
+        bytes[1..].copy_from_slice(self.id.as_slice());
+        bytes
     }
 }
 
-impl From<&[u8]> for Namespace {
-    fn from(bytes: &[u8]) -> Self {
-        assert_eq!(bytes.len(), 29);
-        let version = bytes[0];
-        let mut id = [0u8; 28];
-        id.copy_from_slice(&bytes[1..29]);
-        Self {
-            version: version.into(),
-            id: id.into(),
+impl NamespaceNode {
+    /// Serializes the node as `min || max || digest`.
+    pub fn to_bytes(&self) -> [u8; 90] {
+        let mut bytes = [0u8; 90];
+        bytes[0..29].copy_from_slice(&self.min.to_bytes());
+        bytes[29..58].copy_from_slice(&self.max.to_bytes());
+        bytes[58..90].copy_from_slice(self.digest.as_slice());
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for Namespace {
+    type Error = eyre::Report;
+
+    fn try_from(bytes: &[u8]) -> eyre::Result<Self> {
+        if bytes.len() != 29 {
+            return Err(eyre!("invalid namespace length: {}", bytes.len()));
         }
+        Namespace::new(bytes[0], &bytes[1..29])
     }
 }
 
-impl From<&[u8]> for NamespaceNode {
-    fn from(bytes: &[u8]) -> Self {
+impl TryFrom<&[u8]> for NamespaceNode {
+    type Error = eyre::Report;
+
+    fn try_from(bytes: &[u8]) -> eyre::Result<Self> {
         const LEN: usize = 29 * 2 + 32;
-        let bytes: &[u8; LEN] = bytes.try_into().expect("invalid length");
-        let min = Namespace::from(&bytes[0..29]);
-        let max = Namespace::from(&bytes[29..58]);
+        let bytes: &[u8; LEN] = bytes
+            .try_into()
+            .map_err(|_| eyre!("invalid namespace node length: {}", bytes.len()))?;
+        let min = Namespace::try_from(&bytes[0..29])?;
+        let max = Namespace::try_from(&bytes[29..58])?;
         let digest = B256::from_slice(&bytes[58..90]);
-        NamespaceNode { min, max, digest }
+        Ok(NamespaceNode { min, max, digest })
     }
 }
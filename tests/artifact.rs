@@ -0,0 +1,149 @@
+//! Tests of the SP1 proof artifacts read by the host.
+//!
+//! Run with `cargo test --features host,test-utils`, `test-verifier` for the verification of the
+//! proofs and `mock-rpc` for the nonce checks.
+#![cfg(all(feature = "host", feature = "test-utils"))]
+
+use alloy_primitives::{B256, Bytes, U256};
+use alloy_sol_types::SolValue;
+use openvm_blobstream::{
+    guest::{self, Sp1Verifier},
+    host::{
+        artifact::{ProofArtifact, ProofArtifacts},
+        source::EthereumSource,
+    },
+    native::ProofOutputs,
+};
+#[cfg(feature = "mock-rpc")]
+use {
+    alloy_primitives::Address,
+    alloy_provider::ProviderBuilder,
+    openvm_blobstream::{
+        da_oracle::SP1Blobstream,
+        host::mock::{EthereumExchange, MockRpc, RpcFixture},
+    },
+    serde_json::json,
+};
+
+fn artifact() -> eyre::Result<ProofArtifact> {
+    let selector = &guest::verifier_hash(Sp1Verifier::Stub)?[..4];
+    let public_values = ProofOutputs {
+        trustedHeaderHash: B256::ZERO,
+        targetHeaderHash: B256::ZERO,
+        dataCommitment: B256::repeat_byte(0xdc),
+        trustedBlock: 100,
+        targetBlock: 200,
+        validatorBitmap: U256::ZERO,
+    };
+    Ok(ProofArtifact {
+        vkey: guest::program_vkey(Sp1Verifier::Stub)?,
+        proof_nonce: U256::from(7),
+        proof: [selector, &[0; 32]].concat().into(),
+        public_values: public_values.abi_encode().into(),
+    })
+}
+
+#[test]
+fn artifact_is_validated() -> eyre::Result<()> {
+    let artifact = artifact()?;
+    artifact.validate(Sp1Verifier::Stub)?;
+
+    let mut other_program = artifact.clone();
+    other_program.vkey = B256::repeat_byte(1);
+    assert!(other_program.validate(Sp1Verifier::Stub).is_err());
+
+    let mut other_verifier = artifact.clone();
+    other_verifier.proof = Bytes::from_static(&[0; 36]);
+    assert!(other_verifier.validate(Sp1Verifier::Stub).is_err());
+
+    let mut no_outputs = artifact;
+    no_outputs.public_values = Bytes::new();
+    assert!(no_outputs.validate(Sp1Verifier::Stub).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn artifacts_are_an_ethereum_source() -> eyre::Result<()> {
+    let artifact = artifact()?;
+    let path =
+        std::env::temp_dir().join(format!("blobstream-artifact-{}.json", std::process::id()));
+    artifact.save(&path)?;
+    let artifacts = ProofArtifacts::load([&path], Sp1Verifier::Stub)?;
+
+    let (id, event) = artifacts.find_commit(150).await?;
+    assert_eq!(id, artifact.id());
+    assert_eq!(
+        (event.proofNonce, event.startBlock, event.endBlock),
+        (U256::from(7), 100, 200)
+    );
+    let call = artifacts.get_commit_call(id, &event).await?;
+    assert_eq!(
+        (call.proof, call.publicValues),
+        (artifact.proof, artifact.public_values)
+    );
+    assert!(artifacts.find_commit(200).await.is_err());
+    Ok(())
+}
+
+#[cfg(feature = "test-verifier")]
+#[test]
+fn artifact_proof_is_verified() -> eyre::Result<()> {
+    let vkey = B256::repeat_byte(0x5b);
+    let sp1 = Sp1Verifier::Mock { vkey };
+    let mut artifact = artifact()?;
+    artifact.vkey = vkey;
+    artifact.proof = openvm_blobstream::synthetic::sp1_mock_proof(vkey, &artifact.public_values)?;
+    artifact.validate(sp1)?;
+
+    // a proof of other public values, or of another program claiming the key of the verifier
+    let mut other_outputs = artifact.clone();
+    other_outputs.public_values = ProofOutputs {
+        dataCommitment: B256::repeat_byte(0xee),
+        ..other_outputs.outputs()?
+    }
+    .abi_encode()
+    .into();
+    let err = other_outputs.validate(sp1).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid commitHeaderRange proof"),
+        "{err}"
+    );
+    let mut other_program = artifact.clone();
+    other_program.proof = openvm_blobstream::synthetic::sp1_mock_proof(
+        B256::repeat_byte(1),
+        &artifact.public_values,
+    )?;
+    assert!(other_program.validate(sp1).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "mock-rpc")]
+#[tokio::test]
+async fn nonce_is_checked_against_the_contract() -> eyre::Result<()> {
+    let artifact = artifact()?;
+    let serve = |stored: B256| {
+        MockRpc::serve(RpcFixture {
+            celestia: Vec::new(),
+            ethereum: vec![EthereumExchange::new("eth_call", json!(stored))],
+            celestia_node: Vec::new(),
+        })
+    };
+    let contract = |server: &MockRpc| -> eyre::Result<_> {
+        let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+        Ok(SP1Blobstream::new(Address::repeat_byte(0xb5), provider))
+    };
+
+    let server = serve(artifact.outputs()?.dataCommitment).await?;
+    artifact.check_nonce(&contract(&server)?).await?;
+    ProofArtifacts {
+        artifacts: vec![artifact.clone()],
+    }
+    .check_nonces(&contract(&server)?)
+    .await?;
+
+    // the contract stores another commitment under the nonce
+    let server = serve(B256::repeat_byte(0x01)).await?;
+    let err = artifact.check_nonce(&contract(&server)?).await.unwrap_err();
+    assert!(err.to_string().contains("for nonce 7"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! Differential tests of the openvm bn254 precompiles against the default revm implementation.
+//!
+//! Run with `cargo test --features openvm-host`.
+#![cfg(feature = "openvm-host")]
+
+use alloy_primitives::hex;
+use openvm_blobstream::{RevmCrypto, openvm::Crypto};
+use revm::precompile::DefaultCrypto;
+
+const ZERO_G1: [u8; 64] = [0u8; 64];
+const ZERO_G2: [u8; 128] = [0u8; 128];
+
+/// (1, 2)
+const G1_GENERATOR: [u8; 64] = hex!(
+    "0000000000000000000000000000000000000000000000000000000000000001"
+    "0000000000000000000000000000000000000000000000000000000000000002"
+);
+/// (1, p - 2)
+const G1_GENERATOR_NEG: [u8; 64] = hex!(
+    "0000000000000000000000000000000000000000000000000000000000000001"
+    "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"
+);
+/// (1, 3), not on the curve
+const G1_NOT_ON_CURVE: [u8; 64] = hex!(
+    "0000000000000000000000000000000000000000000000000000000000000001"
+    "0000000000000000000000000000000000000000000000000000000000000003"
+);
+/// (p, 2), x is not a canonical field element
+const G1_NON_CANONICAL: [u8; 64] = hex!(
+    "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47"
+    "0000000000000000000000000000000000000000000000000000000000000002"
+);
+
+const G2_GENERATOR: [u8; 128] = hex!(
+    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+);
+/// On the twist curve with x = 1, but outside of the r-torsion subgroup.
+const G2_NOT_IN_SUBGROUP: [u8; 128] = hex!(
+    "0000000000000000000000000000000000000000000000000000000000000000"
+    "0000000000000000000000000000000000000000000000000000000000000001"
+    "0d1271953ed9ea0836846e70a1934187998c7f790cb4d7511b7f8da82de048a4"
+    "2869111d5381f072f8e2728fdb825a51aadd70e52c9830e9ab4b871c0531f1bb"
+);
+/// The generator with its last coordinate bumped, not on the curve.
+const G2_NOT_ON_CURVE: [u8; 128] = hex!(
+    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7dab"
+);
+
+const SCALAR_ZERO: [u8; 32] = [0u8; 32];
+const SCALAR_ONE: [u8; 32] =
+    hex!("0000000000000000000000000000000000000000000000000000000000000001");
+const SCALAR_ORDER: [u8; 32] =
+    hex!("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+const SCALAR_MAX: [u8; 32] = [0xff; 32];
+
+// The revm implementation slices its inputs and expects the exact lengths the precompile entry
+// points hand over, so the differential corpus only contains well-sized inputs.
+fn g1_corpus() -> Vec<&'static [u8]> {
+    vec![
+        &ZERO_G1,
+        &G1_GENERATOR,
+        &G1_GENERATOR_NEG,
+        &G1_NOT_ON_CURVE,
+        &G1_NON_CANONICAL,
+    ]
+}
+
+fn scalar_corpus() -> Vec<&'static [u8]> {
+    vec![&SCALAR_ZERO, &SCALAR_ONE, &SCALAR_ORDER, &SCALAR_MAX]
+}
+
+fn g2_corpus() -> Vec<&'static [u8]> {
+    vec![
+        &ZERO_G2,
+        &G2_GENERATOR,
+        &G2_NOT_IN_SUBGROUP,
+        &G2_NOT_ON_CURVE,
+    ]
+}
+
+fn right_pad(input: &[u8], len: usize) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    padded.resize(len, 0);
+    padded
+}
+
+#[test]
+fn g1_add_matches_revm() {
+    for p1 in g1_corpus() {
+        for p2 in g1_corpus() {
+            assert_eq!(
+                Crypto.bn254_g1_add(p1, p2),
+                DefaultCrypto.bn254_g1_add(p1, p2),
+                "p1 = {}, p2 = {}",
+                hex::encode(p1),
+                hex::encode(p2),
+            );
+        }
+    }
+}
+
+#[test]
+fn g1_mul_matches_revm() {
+    for point in g1_corpus() {
+        for scalar in scalar_corpus() {
+            assert_eq!(
+                Crypto.bn254_g1_mul(point, scalar),
+                DefaultCrypto.bn254_g1_mul(point, scalar),
+                "point = {}, scalar = {}",
+                hex::encode(point),
+                hex::encode(scalar),
+            );
+        }
+    }
+}
+
+#[test]
+fn pairing_check_single_pair_matches_revm() {
+    for g1 in g1_corpus() {
+        for g2 in g2_corpus() {
+            let pairs = [(g1, g2)];
+            assert_eq!(
+                Crypto.bn254_pairing_check(&pairs),
+                DefaultCrypto.bn254_pairing_check(&pairs),
+                "g1 = {}, g2 = {}",
+                hex::encode(g1),
+                hex::encode(g2),
+            );
+        }
+    }
+}
+
+#[test]
+fn pairing_check_multi_pair_matches_revm() {
+    let cases: Vec<Vec<(&[u8], &[u8])>> = vec![
+        vec![],
+        vec![
+            (&G1_GENERATOR[..], &G2_GENERATOR[..]),
+            (&G1_GENERATOR_NEG[..], &G2_GENERATOR[..]),
+        ],
+        vec![
+            (&G1_GENERATOR[..], &G2_GENERATOR[..]),
+            (&G1_GENERATOR[..], &G2_GENERATOR[..]),
+        ],
+        // an invalid point must be rejected even if its pair is skipped
+        vec![
+            (&ZERO_G1[..], &G2_NOT_IN_SUBGROUP[..]),
+            (&G1_GENERATOR[..], &ZERO_G2[..]),
+        ],
+        vec![(&G1_NOT_ON_CURVE[..], &ZERO_G2[..])],
+        vec![
+            (&ZERO_G1[..], &ZERO_G2[..]),
+            (&G1_GENERATOR[..], &G2_GENERATOR[..]),
+        ],
+    ];
+    for pairs in cases {
+        assert_eq!(
+            Crypto.bn254_pairing_check(&pairs),
+            DefaultCrypto.bn254_pairing_check(&pairs),
+            "pairs = {pairs:?}",
+        );
+    }
+}
+
+#[test]
+fn short_inputs_are_right_padded() {
+    let cases: [(&[u8], &[u8]); 3] = [
+        (&[][..], &[][..]),
+        (&G1_GENERATOR[..63], &G1_GENERATOR[..1]),
+        (&G1_GENERATOR[..], &G1_GENERATOR_NEG[..33]),
+    ];
+    for (p1, p2) in cases {
+        assert_eq!(
+            Crypto.bn254_g1_add(p1, p2),
+            DefaultCrypto.bn254_g1_add(&right_pad(p1, 64), &right_pad(p2, 64)),
+        );
+    }
+    let scalars: [&[u8]; 3] = [&SCALAR_ONE[..31], &SCALAR_MAX[..1], &[]];
+    for scalar in scalars {
+        assert_eq!(
+            Crypto.bn254_g1_mul(&G1_GENERATOR, scalar),
+            DefaultCrypto.bn254_g1_mul(&G1_GENERATOR, &right_pad(scalar, 32)),
+        );
+    }
+}
+
+#[test]
+fn pairing_check_rejects_bad_lengths() {
+    let cases: [(&[u8], &[u8]); 3] = [
+        (&G1_GENERATOR[..63], &G2_GENERATOR[..]),
+        (&G1_GENERATOR[..], &G2_GENERATOR[..127]),
+        (&G2_GENERATOR[..], &G1_GENERATOR[..]),
+    ];
+    for pair in cases {
+        assert_eq!(
+            Crypto.bn254_pairing_check(&[pair]),
+            Err(revm::precompile::PrecompileError::Bn254PairLength),
+        );
+    }
+}
+
+#[test]
+fn g2_subgroup_is_checked() {
+    let pairs = [(&G1_GENERATOR[..], &G2_NOT_IN_SUBGROUP[..])];
+    assert!(Crypto.bn254_pairing_check(&pairs).is_err());
+}
@@ -0,0 +1,184 @@
+//! EIP-196 and EIP-197 test vectors for the openvm bn254 precompiles, run on the host with the
+//! software fallbacks of the openvm guest libraries.
+//!
+//! The `chfast` vectors are taken from the go-ethereum precompile test suite, the remaining
+//! vectors are derived from the group generators.
+//!
+//! Run with `cargo test --features openvm-host`.
+#![cfg(feature = "openvm-host")]
+
+use alloy_primitives::hex;
+use openvm_blobstream::{RevmCrypto, openvm::Crypto};
+use revm::precompile::PrecompileError;
+
+const G1: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                  0000000000000000000000000000000000000000000000000000000000000002";
+const G1_NEG: &str = "0000000000000000000000000000000000000000000000000000000000000001\
+                      30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+const G1_DOUBLE: &str = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                         15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+const G1_ZERO: &str = "0000000000000000000000000000000000000000000000000000000000000000\
+                       0000000000000000000000000000000000000000000000000000000000000000";
+const G2: &str = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                  1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                  090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                  12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+const G2_DOUBLE: &str = "203e205db4f19b37b60121b83a7333706db86431c6d835849957ed8c3928ad79\
+                         27dc7234fd11d3e8c36c59277c3e6f149d5cd3cfa9a62aee49f8130962b4b3b9\
+                         195e8aa5b7827463722b8c153931579d3505566b4edf48d498e185f0509de152\
+                         04bb53b8977e5f92a0bc372742c4830944a59b4fe6b1c0466e2a6dad122b5d2e";
+const SCALAR_ORDER: &str = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+const SCALAR_ORDER_MINUS_ONE: &str =
+    "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000";
+
+/// Splits the input the same way the EIP-196 ECADD precompile does.
+fn g1_add(input: &str) -> Result<[u8; 64], PrecompileError> {
+    let mut input = hex::decode(input).unwrap();
+    input.resize(128, 0);
+    Crypto.bn254_g1_add(&input[..64], &input[64..])
+}
+
+/// Splits the input the same way the EIP-196 ECMUL precompile does.
+fn g1_mul(input: &str) -> Result<[u8; 64], PrecompileError> {
+    let mut input = hex::decode(input).unwrap();
+    input.resize(96, 0);
+    Crypto.bn254_g1_mul(&input[..64], &input[64..])
+}
+
+/// Splits the input the same way the EIP-197 ECPAIRING precompile does.
+fn pairing_check(input: &str) -> Result<bool, PrecompileError> {
+    let input = hex::decode(input).unwrap();
+    assert_eq!(input.len() % 192, 0, "invalid pairing input length");
+    let pairs = input
+        .chunks_exact(192)
+        .map(|pair| (&pair[..64], &pair[64..]))
+        .collect::<Vec<_>>();
+    Crypto.bn254_pairing_check(&pairs)
+}
+
+#[test]
+fn eip196_add() {
+    let vectors = [
+        (
+            "chfast1",
+            "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+             063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+             07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+             06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7"
+                .to_string(),
+            "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+             301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+        ),
+        ("empty", String::new(), G1_ZERO),
+        ("generator_plus_zero", G1.to_string(), G1),
+        ("generator_doubled", [G1, G1].concat(), G1_DOUBLE),
+        ("generator_plus_negation", [G1, G1_NEG].concat(), G1_ZERO),
+    ];
+    for (name, input, expected) in vectors {
+        let output = g1_add(&input).unwrap_or_else(|e| panic!("{name}: {e:?}"));
+        assert_eq!(hex::encode(output), expected, "{name}");
+    }
+}
+
+#[test]
+fn eip196_add_invalid() {
+    let not_on_curve = "0000000000000000000000000000000000000000000000000000000000000001\
+                        0000000000000000000000000000000000000000000000000000000000000003";
+    assert_eq!(
+        g1_add(&[G1, not_on_curve].concat()),
+        Err(PrecompileError::Bn254AffineGFailedToCreate)
+    );
+    let x_not_in_field = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47\
+                          0000000000000000000000000000000000000000000000000000000000000002";
+    assert_eq!(
+        g1_add(x_not_in_field),
+        Err(PrecompileError::Bn254FieldPointNotAMember)
+    );
+}
+
+#[test]
+fn eip196_mul() {
+    let vectors = [
+        (
+            "chfast1",
+            "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+             21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204\
+             00000000000000000000000000000000000000000000000011138ce750fa15c2"
+                .to_string(),
+            "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c\
+             031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
+        ),
+        ("empty", String::new(), G1_ZERO),
+        ("generator_times_zero", G1.to_string(), G1_ZERO),
+        (
+            "generator_times_two",
+            [
+                G1,
+                "0000000000000000000000000000000000000000000000000000000000000002",
+            ]
+            .concat(),
+            G1_DOUBLE,
+        ),
+        (
+            "generator_times_order_minus_one",
+            [G1, SCALAR_ORDER_MINUS_ONE].concat(),
+            G1_NEG,
+        ),
+        (
+            "generator_times_order",
+            [G1, SCALAR_ORDER].concat(),
+            G1_ZERO,
+        ),
+        (
+            "zero_times_scalar",
+            [G1_ZERO, SCALAR_ORDER_MINUS_ONE].concat(),
+            G1_ZERO,
+        ),
+    ];
+    for (name, input, expected) in vectors {
+        let output = g1_mul(&input).unwrap_or_else(|e| panic!("{name}: {e:?}"));
+        assert_eq!(hex::encode(output), expected, "{name}");
+    }
+}
+
+#[test]
+fn eip197_pairing() {
+    let vectors = [
+        ("empty", String::new(), true),
+        ("one_pair", [G1, G2].concat(), false),
+        (
+            "generator_and_negation",
+            [G1, G2, G1_NEG, G2].concat(),
+            true,
+        ),
+        (
+            "doubled_on_both_sides",
+            [G1_DOUBLE, G2, G1_NEG, G2_DOUBLE].concat(),
+            true,
+        ),
+        ("same_pair_twice", [G1, G2, G1, G2].concat(), false),
+        (
+            "zero_pair_skipped",
+            [G1_ZERO, G2, G1, G2, G1_NEG, G2].concat(),
+            true,
+        ),
+    ];
+    for (name, input, expected) in vectors {
+        let output = pairing_check(&input).unwrap_or_else(|e| panic!("{name}: {e:?}"));
+        assert_eq!(output, expected, "{name}");
+    }
+}
+
+#[test]
+fn eip197_pairing_invalid() {
+    let g2_not_on_curve = [&G2[..G2.len() - 1], "b"].concat();
+    assert_eq!(
+        pairing_check(&[G1, &g2_not_on_curve].concat()),
+        Err(PrecompileError::Bn254AffineGFailedToCreate)
+    );
+    let g1_not_on_curve = [&G1[..G1.len() - 1], "3"].concat();
+    assert_eq!(
+        pairing_check(&[&g1_not_on_curve, G2].concat()),
+        Err(PrecompileError::Bn254AffineGFailedToCreate)
+    );
+}
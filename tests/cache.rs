@@ -0,0 +1,219 @@
+//! Tests of the on-disk cache of the host.
+//!
+//! Run with `cargo test --features mock-rpc`.
+#![cfg(feature = "mock-rpc")]
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_sol_types::{SolCall, SolValue};
+use openvm_blobstream::{
+    da_oracle::{DataCommitmentStored, SP1Blobstream, commitHeaderRangeCall},
+    host::{
+        cache::{CachedBlobstream, CachedCelestia, EntryKey},
+        mock::{self, CelestiaExchange, EthereumExchange, MockRpc, RpcFixture},
+        source::{CelestiaSource, EthereumSource},
+    },
+    native::ProofOutputs,
+};
+use serde_json::json;
+use std::path::Path;
+
+fn cache_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("blobstream-cache-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn inclusion_proofs_are_cached() -> eyre::Result<()> {
+    let dir = cache_dir("inclusion-proofs");
+    let fixture = RpcFixture {
+        celestia: vec![CelestiaExchange {
+            path: "data_root_inclusion_proof".to_string(),
+            query: [
+                ("height".to_string(), "12".to_string()),
+                ("start".to_string(), "10".to_string()),
+                ("end".to_string(), "14".to_string()),
+            ]
+            .into(),
+            response: json!({ "result": { "proof": {
+                "total": "4",
+                "index": "2",
+                "aunts": ["AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE="],
+            }}}),
+        }],
+        ethereum: Vec::new(),
+        celestia_node: Vec::new(),
+    };
+    let fetched = CachedCelestia {
+        source: fixture,
+        dir: dir.clone(),
+    }
+    .get_data_root_inclusion_proof(12, 10, 14)
+    .await?;
+
+    // served from the cache, the source has nothing recorded
+    let cached = CachedCelestia {
+        source: RpcFixture::default(),
+        dir: dir.clone(),
+    };
+    let proof = cached.get_data_root_inclusion_proof(12, 10, 14).await?;
+    assert_eq!(
+        (proof.total, proof.index, proof.aunts),
+        (fetched.total, fetched.index, fetched.aunts)
+    );
+    assert!(
+        cached
+            .get_data_root_inclusion_proof(13, 10, 14)
+            .await
+            .is_err()
+    );
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+const BLOBSTREAM: Address = Address::repeat_byte(0xb5);
+const ETH_BLOCK: u64 = 0x100000;
+
+fn commitment() -> (DataCommitmentStored, commitHeaderRangeCall) {
+    let event = DataCommitmentStored {
+        proofNonce: U256::from(7),
+        startBlock: 100,
+        endBlock: 200,
+        dataCommitment: B256::repeat_byte(0xdc),
+    };
+    let public_values = ProofOutputs {
+        trustedHeaderHash: B256::ZERO,
+        targetHeaderHash: B256::ZERO,
+        dataCommitment: event.dataCommitment,
+        trustedBlock: event.startBlock,
+        targetBlock: event.endBlock,
+        validatorBitmap: U256::ZERO,
+    };
+    let call = commitHeaderRangeCall {
+        proof: Bytes::from_static(&[0x01; 4]),
+        publicValues: public_values.abi_encode().into(),
+    };
+    (event, call)
+}
+
+/// A chain whose block `ETH_BLOCK` has hash `block_hash` and holds the commit tx `tx_hash`, 10
+/// blocks below the head.
+fn chain(block_hash: B256, tx_hash: B256) -> RpcFixture {
+    let (event, call) = commitment();
+    RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            EthereumExchange::new("eth_blockNumber", json!(format!("{:#x}", ETH_BLOCK + 10))),
+            EthereumExchange::new(
+                "eth_getLogs",
+                json!([mock::commitment_log(
+                    BLOBSTREAM, &event, ETH_BLOCK, block_hash, tx_hash
+                )]),
+            ),
+            EthereumExchange::new(
+                "eth_getTransactionReceipt",
+                mock::receipt(tx_hash, ETH_BLOCK, block_hash),
+            ),
+            EthereumExchange::new(
+                "eth_getTransactionByHash",
+                mock::transaction(
+                    tx_hash,
+                    ETH_BLOCK,
+                    block_hash,
+                    BLOBSTREAM,
+                    &call.abi_encode(),
+                ),
+            ),
+        ],
+        celestia_node: Vec::new(),
+    }
+}
+
+fn cached_blobstream(
+    server: &MockRpc,
+    dir: &Path,
+) -> eyre::Result<CachedBlobstream<impl Provider + use<>>> {
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+    Ok(CachedBlobstream::new(
+        SP1Blobstream::new(BLOBSTREAM, provider),
+        dir,
+    ))
+}
+
+#[tokio::test]
+async fn commitments_are_looked_up_by_file_name() -> eyre::Result<()> {
+    let dir = cache_dir("commitments");
+    let block_hash = B256::repeat_byte(0x01);
+    let tx_hash = B256::repeat_byte(0xaa);
+    let server = MockRpc::serve(chain(block_hash, tx_hash)).await?;
+    let cache = cached_blobstream(&server, &dir)?;
+    let fetched = cache.get(150).await?;
+    assert_eq!(
+        (fetched.eth_tx_hash, fetched.eth_block_hash),
+        (tx_hash, block_hash)
+    );
+    assert_eq!(
+        cache.index()?,
+        [EntryKey {
+            start_block: 100,
+            end_block: 200,
+            proof_nonce: U256::from(7),
+        }]
+    );
+
+    // an unreadable entry of another range is never read
+    let commitments = dir.join(BLOBSTREAM.to_string()).join("commitments");
+    std::fs::write(commitments.join("200-300-8.json"), "not json")?;
+
+    // served from the cache, the chain only tells the commit tx is still in its block
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            EthereumExchange::new("eth_blockNumber", json!(format!("{:#x}", ETH_BLOCK + 10))),
+            EthereumExchange::new(
+                "eth_getTransactionReceipt",
+                mock::receipt(tx_hash, ETH_BLOCK, block_hash),
+            ),
+        ],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let cache = cached_blobstream(&server, &dir)?;
+    assert_eq!(cache.get(199).await?, fetched);
+    let (event, call) = commitment();
+    let (found_tx_hash, found) = cache.find_commit(100).await?;
+    assert_eq!(
+        (found_tx_hash, found.proofNonce, found.dataCommitment),
+        (tx_hash, event.proofNonce, event.dataCommitment)
+    );
+    let found = cache.get_commit_call(tx_hash, &event).await?;
+    assert_eq!(found.abi_encode(), call.abi_encode());
+    assert!(cache.get(250).await.is_err());
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn reorganized_commitments_are_refetched() -> eyre::Result<()> {
+    let dir = cache_dir("reorg");
+    let server = MockRpc::serve(chain(B256::repeat_byte(0x01), B256::repeat_byte(0xaa))).await?;
+    cached_blobstream(&server, &dir)?.get(150).await?;
+
+    // block `ETH_BLOCK` was replaced, with the commitment in another tx
+    let block_hash = B256::repeat_byte(0x02);
+    let tx_hash = B256::repeat_byte(0xab);
+    let server = MockRpc::serve(chain(block_hash, tx_hash)).await?;
+    let cache = cached_blobstream(&server, &dir)?;
+    let entry = cache.get(150).await?;
+    assert_eq!(
+        (entry.eth_tx_hash, entry.eth_block_hash),
+        (tx_hash, block_hash)
+    );
+    assert_eq!(cache.entries()?, [entry]);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! Tests of the celestia-node client against the JSON-RPC mock server, serving a synthetic block.
+//!
+//! Run with `cargo test --features mock-rpc,test-utils`.
+#![cfg(all(feature = "mock-rpc", feature = "test-utils"))]
+
+use alloy_primitives::{B256, U256, hex};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Sp1Verifier},
+    host::{
+        BlockHeader,
+        celestia_node::CelestiaNodeClient,
+        mock::{EthereumExchange, MockRpc, RpcFixture},
+        source::CelestiaSource,
+    },
+    native,
+    native::shares::Blob,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::{Namespace, NamespaceNode, SharesProof},
+};
+use serde_json::{Value, json};
+
+const HEIGHT: u64 = 42;
+const COMMITMENT: [u8; 32] = [0xcc; 32];
+const BLOCK_HASH: B256 = B256::repeat_byte(0xbb);
+
+fn base64_all<T: AsRef<[u8]>>(items: impl IntoIterator<Item = T>) -> Vec<String> {
+    items.into_iter().map(|item| BASE64.encode(item)).collect()
+}
+
+fn exchange(method: &str, result: Value) -> EthereumExchange {
+    EthereumExchange {
+        method: method.to_string(),
+        params: None,
+        result,
+    }
+}
+
+/// A node whose block `HEIGHT` holds the second of two blobs, committed in a range of 3 blocks.
+fn node_fixture(namespace: &Namespace) -> (RpcFixture, DataCommitment) {
+    let blobs = [
+        Blob {
+            namespace: Namespace::new(0, &[0x00; 28]).unwrap(),
+            data: vec![0x11; 700],
+        },
+        Blob {
+            namespace: namespace.clone(),
+            data: vec![0x22; 3_000],
+        },
+    ];
+    let square = ExtendedSquare::new(&blobs);
+    let commitment = DataCommitment::new(
+        HEIGHT - 1,
+        [
+            B256::repeat_byte(0x01),
+            square.data_root(),
+            B256::repeat_byte(0x02),
+        ],
+    );
+    let attestation = commitment.attestation_proof(HEIGHT, U256::from(1)).unwrap();
+    let proof = square.shares_proof(1, attestation.clone());
+
+    let share_proofs = proof
+        .shareProofs
+        .iter()
+        .map(|proof| {
+            json!({
+                "start": proof.beginKey.to::<u64>(),
+                "end": proof.endKey.to::<u64>(),
+                "nodes": base64_all(proof.sideNodes.iter().map(NamespaceNode::to_bytes)),
+                "is_max_namespace_ignored": true,
+            })
+        })
+        .collect::<Vec<_>>();
+    let celestia_node = vec![
+        exchange(
+            "blob.Get",
+            json!({
+                "namespace": BASE64.encode(namespace.to_bytes()),
+                "data": BASE64.encode(&blobs[1].data),
+                "share_version": 0,
+                "commitment": BASE64.encode(COMMITMENT),
+                "index": square.blobs[1].start,
+            }),
+        ),
+        exchange("blob.GetProof", json!(share_proofs)),
+        exchange(
+            "header.GetByHeight",
+            json!({
+                "header": {
+                    "height": HEIGHT.to_string(),
+                    "data_hash": hex::encode_upper(square.data_root()),
+                },
+                "commit": { "block_id": { "hash": hex::encode_upper(BLOCK_HASH) } },
+                "dah": {
+                    "row_roots": base64_all(square.row_roots.iter().map(NamespaceNode::to_bytes)),
+                    "column_roots": base64_all(square.column_roots.iter().map(NamespaceNode::to_bytes)),
+                },
+            }),
+        ),
+        exchange(
+            "blobstream.GetDataRootTupleInclusionProof",
+            json!({
+                "total": attestation.proof.numLeaves.to::<u64>(),
+                "index": attestation.proof.key.to::<u64>(),
+                "aunts": base64_all(&attestation.proof.sideNodes),
+            }),
+        ),
+    ];
+    (
+        RpcFixture {
+            celestia: Vec::new(),
+            ethereum: Vec::new(),
+            celestia_node,
+        },
+        commitment,
+    )
+}
+
+#[tokio::test]
+async fn node_proofs_validate() -> eyre::Result<()> {
+    let namespace = Namespace::new(0, &[0x07; 28])?;
+    let (fixture, commitment) = node_fixture(&namespace);
+    let server = MockRpc::serve(fixture).await?;
+    let client = CelestiaNodeClient::new(server.celestia_node_url(), Some("token".to_string()));
+
+    let (data_hash, tx_proof) = client
+        .get_blob_proof(HEIGHT, &namespace, &COMMITMENT)
+        .await?;
+    let inclusion_proof = client
+        .get_data_root_inclusion_proof(HEIGHT, commitment.start_block, commitment.end_block())
+        .await?;
+    let input = GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof()?,
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: SharesProof::new(HEIGHT, data_hash, U256::from(1), inclusion_proof, tx_proof)?,
+    };
+    guest::validate_with(input, Backend::Both, Sp1Verifier::Stub)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn node_namespace_proofs_verify() -> eyre::Result<()> {
+    let namespace = Namespace::new(0, &[0x07; 28])?;
+    let absent = Namespace::new(0, &[0x05; 28])?;
+    let blobs = [
+        Blob {
+            namespace: Namespace::new(0, &[0x00; 28])?,
+            data: vec![0x11; 4_000],
+        },
+        Blob {
+            namespace: namespace.clone(),
+            data: vec![0x22; 6_000],
+        },
+    ];
+    let square = ExtendedSquare::new(&blobs);
+    let header = json!({
+        "header": {
+            "height": HEIGHT.to_string(),
+            "data_hash": hex::encode_upper(square.data_root()),
+        },
+        "commit": { "block_id": { "hash": hex::encode_upper(BLOCK_HASH) } },
+        "dah": {
+            "row_roots": base64_all(square.row_roots.iter().map(NamespaceNode::to_bytes)),
+            "column_roots": base64_all(square.column_roots.iter().map(NamespaceNode::to_bytes)),
+        },
+    });
+    // the rows holding the namespace, as the node returns them
+    let namespace_data = |namespace: &Namespace| {
+        let proof = square.namespace_proof(namespace);
+        proof
+            .rows
+            .iter()
+            .filter(|row| native::namespace::covers(&row.root, namespace))
+            .map(|row| {
+                json!({
+                    "shares": base64_all(&row.shares),
+                    "proof": {
+                        "start": row.proof.beginKey.to::<u64>(),
+                        "end": row.proof.endKey.to::<u64>(),
+                        "nodes": base64_all(row.proof.sideNodes.iter().map(NamespaceNode::to_bytes)),
+                        "leaf_hash": row
+                            .absence_leaf
+                            .as_ref()
+                            .map(|leaf| BASE64.encode(leaf.to_bytes()))
+                            .unwrap_or_default(),
+                        "is_max_namespace_ignored": true,
+                    },
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for namespace in [namespace, absent] {
+        let fixture = RpcFixture {
+            celestia: Vec::new(),
+            ethereum: Vec::new(),
+            celestia_node: vec![
+                exchange("header.GetByHeight", header.clone()),
+                exchange("share.GetNamespaceData", json!(namespace_data(&namespace))),
+            ],
+        };
+        let server = MockRpc::serve(fixture.clone()).await?;
+        let client = CelestiaNodeClient::new(server.celestia_node_url(), None);
+        let (data_hash, proof) = client.get_namespace_proof(HEIGHT, &namespace).await?;
+        assert_eq!(data_hash, square.data_root());
+        assert!(native::namespace::verify(&proof, &data_hash));
+        assert_eq!(
+            proof.shares().collect::<Vec<_>>(),
+            square
+                .namespace_proof(&namespace)
+                .shares()
+                .collect::<Vec<_>>()
+        );
+
+        // the same proof through the sources
+        assert_eq!(
+            CelestiaSource::get_namespace_shares(&client, HEIGHT, &namespace).await?,
+            proof
+        );
+        assert_eq!(
+            fixture.get_namespace_shares(HEIGHT, &namespace).await?,
+            proof
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn node_headers_are_checked() -> eyre::Result<()> {
+    let namespace = Namespace::new(0, &[0x07; 28])?;
+    let (fixture, commitment) = node_fixture(&namespace);
+    let server = MockRpc::serve(fixture).await?;
+    let client = CelestiaNodeClient::new(server.celestia_node_url(), None);
+    assert_eq!(
+        CelestiaSource::get_header(&client, HEIGHT).await?,
+        BlockHeader {
+            height: HEIGHT,
+            hash: BLOCK_HASH,
+            data_hash: commitment.tuples[1].dataRoot,
+        }
+    );
+    // the mock node answers any height with the header of `HEIGHT`
+    let err = CelestiaSource::get_header(&client, HEIGHT + 1)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("returned as block"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn node_errors_are_returned() -> eyre::Result<()> {
+    let server = MockRpc::serve(RpcFixture::default()).await?;
+    let client = CelestiaNodeClient::new(server.celestia_node_url(), None);
+    let err = client.get_header(HEIGHT).await.unwrap_err();
+    assert!(
+        err.to_string().contains("header.GetByHeight failed"),
+        "{err}"
+    );
+    Ok(())
+}
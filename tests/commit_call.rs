@@ -0,0 +1,178 @@
+//! Tests of the location of `commitHeaderRange` calls in wrapped calldata.
+//!
+//! Run with `cargo test --features host`.
+#![cfg(feature = "host")]
+
+use alloy_primitives::{Address, B256, Bytes, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use openvm_blobstream::{
+    da_oracle::{BlobstreamX, DataCommitmentStored, commitHeaderRangeCall},
+    host::{
+        commit_call::{
+            EntryPointV07, MultiSend, Multicall3, Safe, SimpleAccount, find_in_calldata,
+        },
+        deployments::ContractVersion,
+    },
+    native::ProofOutputs,
+};
+
+const BLOBSTREAM: Address = Address::repeat_byte(0xb5);
+
+/// The encoded call to `BLOBSTREAM` found in `input`.
+fn find(to: Address, input: &[u8], event: &DataCommitmentStored) -> Option<Vec<u8>> {
+    find_in_calldata(BLOBSTREAM, ContractVersion::Sp1Blobstream, to, input, event).map(Into::into)
+}
+
+fn commit(start_block: u64, end_block: u64) -> (commitHeaderRangeCall, DataCommitmentStored) {
+    let data_commitment = B256::repeat_byte(start_block as u8);
+    let public_values = ProofOutputs {
+        trustedHeaderHash: B256::ZERO,
+        targetHeaderHash: B256::ZERO,
+        dataCommitment: data_commitment,
+        trustedBlock: start_block,
+        targetBlock: end_block,
+        validatorBitmap: U256::ZERO,
+    };
+    let call = commitHeaderRangeCall {
+        proof: Bytes::from_static(&[0; 4]),
+        publicValues: public_values.abi_encode().into(),
+    };
+    let event = DataCommitmentStored {
+        proofNonce: U256::from(start_block),
+        startBlock: start_block,
+        endBlock: end_block,
+        dataCommitment: data_commitment,
+    };
+    (call, event)
+}
+
+fn exec_transaction(to: Address, data: Bytes, operation: u8) -> Bytes {
+    Safe::execTransactionCall {
+        to,
+        value: U256::ZERO,
+        data,
+        operation,
+        safeTxGas: U256::ZERO,
+        baseGas: U256::ZERO,
+        gasPrice: U256::ZERO,
+        gasToken: Address::ZERO,
+        refundReceiver: Address::ZERO,
+        signatures: Bytes::new(),
+    }
+    .abi_encode()
+    .into()
+}
+
+#[test]
+fn direct_call_is_found() {
+    let (call, event) = commit(10, 20);
+    let input = call.abi_encode();
+    assert_eq!(find(BLOBSTREAM, &input, &event), Some(call.abi_encode()));
+    // not the contract
+    assert_eq!(find(Address::repeat_byte(1), &input, &event), None);
+    // not the event
+    let (_, other) = commit(20, 30);
+    assert_eq!(find(BLOBSTREAM, &input, &other), None);
+}
+
+#[test]
+fn safe_call_is_found() {
+    let (call, event) = commit(10, 20);
+    let input = exec_transaction(BLOBSTREAM, call.abi_encode().into(), 0);
+    let safe = Address::repeat_byte(0x5a);
+    assert_eq!(find(safe, &input, &event), Some(call.abi_encode()));
+}
+
+#[test]
+fn safe_multi_send_is_found() {
+    let calls = [commit(10, 20), commit(20, 30)];
+    let mut transactions = Vec::new();
+    for (call, _) in &calls {
+        let data = call.abi_encode();
+        transactions.push(0);
+        transactions.extend_from_slice(BLOBSTREAM.as_slice());
+        transactions.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+        transactions.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+        transactions.extend_from_slice(&data);
+    }
+    let multi_send = MultiSend::multiSendCall {
+        transactions: transactions.into(),
+    };
+    let input = exec_transaction(
+        Address::repeat_byte(0x40),
+        multi_send.abi_encode().into(),
+        1,
+    );
+    let safe = Address::repeat_byte(0x5a);
+    for (call, event) in calls {
+        assert_eq!(find(safe, &input, &event), Some(call.abi_encode()));
+    }
+}
+
+#[test]
+fn multicall_picks_the_call_of_the_event() {
+    let calls = [commit(10, 20), commit(20, 30)];
+    let input = Multicall3::aggregate3Call {
+        calls: calls
+            .iter()
+            .map(|(call, _)| Multicall3::Call3 {
+                target: BLOBSTREAM,
+                allowFailure: false,
+                callData: call.abi_encode().into(),
+            })
+            .collect(),
+    }
+    .abi_encode();
+    let multicall = Address::repeat_byte(0xca);
+    for (call, event) in calls {
+        assert_eq!(find(multicall, &input, &event), Some(call.abi_encode()));
+    }
+}
+
+#[test]
+fn user_operation_is_found() {
+    let (call, event) = commit(10, 20);
+    let account = Address::repeat_byte(0xac);
+    let execute = SimpleAccount::executeCall {
+        dest: BLOBSTREAM,
+        value: U256::ZERO,
+        func: call.abi_encode().into(),
+    };
+    let input = EntryPointV07::handleOpsCall {
+        ops: vec![EntryPointV07::PackedUserOperation {
+            sender: account,
+            nonce: U256::ZERO,
+            initCode: Bytes::new(),
+            callData: execute.abi_encode().into(),
+            accountGasLimits: B256::ZERO,
+            preVerificationGas: U256::ZERO,
+            gasFees: B256::ZERO,
+            paymasterAndData: Bytes::new(),
+            signature: Bytes::new(),
+        }],
+        beneficiary: Address::ZERO,
+    }
+    .abi_encode();
+    let entry_point = Address::repeat_byte(0xe7);
+    assert_eq!(find(entry_point, &input, &event), Some(call.abi_encode()));
+}
+
+#[test]
+fn blobstream_x_call_is_found_by_its_version() {
+    let (call, event) = commit(10, 20);
+    let legacy = BlobstreamX::commitHeaderRangeCall { _targetBlock: 20 }.abi_encode();
+    let input = exec_transaction(BLOBSTREAM, legacy.clone().into(), 0);
+    let safe = Address::repeat_byte(0x5a);
+    let find_legacy = |input: &[u8], event: &DataCommitmentStored| {
+        find_in_calldata(BLOBSTREAM, ContractVersion::BlobstreamX, safe, input, event)
+            .map(Vec::from)
+    };
+    assert_eq!(find_legacy(&input, &event), Some(legacy));
+    assert_eq!(find(safe, &input, &event), None);
+
+    // another target block, or an SP1 Blobstream call
+    let (_, other) = commit(20, 30);
+    assert_eq!(find_legacy(&input, &other), None);
+    let input = exec_transaction(BLOBSTREAM, call.abi_encode().into(), 0);
+    assert_eq!(find_legacy(&input, &event), None);
+}
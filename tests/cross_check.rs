@@ -0,0 +1,88 @@
+//! Tests of the checks of fetched data, on inputs proving blobs of synthetic squares.
+//!
+//! Run with `cargo test --features mock-rpc,test-utils`.
+#![cfg(all(feature = "mock-rpc", feature = "test-utils"))]
+
+use alloy_primitives::{B256, U256, hex};
+use openvm_blobstream::{
+    GuestInput,
+    host::{
+        check::{check_proofs, cross_check},
+        mock::{CelestiaExchange, RpcFixture},
+    },
+    native::shares::Blob,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::Namespace,
+};
+use serde_json::json;
+
+const HEIGHT: u64 = 50;
+
+fn synthetic_input() -> GuestInput {
+    let square = ExtendedSquare::new(&[Blob {
+        namespace: Namespace::new(0, &[0x03; 28]).unwrap(),
+        data: vec![0x5c; 1_500],
+    }]);
+    let commitment = DataCommitment::new(HEIGHT, [square.data_root(), B256::repeat_byte(0x01)]);
+    let attestation = commitment.attestation_proof(HEIGHT, U256::from(1)).unwrap();
+    GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof().unwrap(),
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: square.shares_proof(0, attestation),
+    }
+}
+
+/// A second source answering `data_hash` for block `HEIGHT`.
+fn second_source(data_hash: B256) -> RpcFixture {
+    RpcFixture {
+        celestia: vec![CelestiaExchange {
+            path: "block".to_string(),
+            query: [("height".to_string(), HEIGHT.to_string())].into(),
+            response: json!({ "result": { "block": { "header": {
+                "data_hash": hex::encode_upper(data_hash),
+            }}}}),
+        }],
+        ethereum: Vec::new(),
+        celestia_node: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn consistent_sources_pass() -> eyre::Result<()> {
+    let input = synthetic_input();
+    let data_root = input.sharesProof.attestationProof.tuple.dataRoot;
+    cross_check(&input, &second_source(data_root)).await
+}
+
+#[tokio::test]
+async fn diverging_data_hash_is_detected() {
+    let input = synthetic_input();
+    let err = cross_check(&input, &second_source(B256::repeat_byte(0xee)))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("differs between sources"), "{err}");
+}
+
+#[test]
+fn tampered_proofs_are_detected() {
+    let mut input = synthetic_input();
+    input.sharesProof.rowRoots[0].digest = !input.sharesProof.rowRoots[0].digest;
+    let err = check_proofs(&input).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "invalid sharesProof");
+
+    let mut input = synthetic_input();
+    input.sharesProof.attestationProof.tuple.dataRoot = B256::repeat_byte(0xee);
+    let err = check_proofs(&input).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "invalid BinaryMerkleTree proof"
+    );
+
+    let mut input = synthetic_input();
+    let mut share = input.sharesProof.data[0].to_vec();
+    share[100] ^= 0x01;
+    input.sharesProof.data[0] = share.into();
+    let err = check_proofs(&input).unwrap_err();
+    assert_eq!(err.to_string(), "guest input rejected");
+    assert_eq!(err.root_cause().to_string(), "invalid sharesProof");
+}
@@ -0,0 +1,160 @@
+//! Tests of the registry of Blobstream deployments.
+//!
+//! Run with `cargo test --features host`, and `mock-rpc` for the lookups of legacy commitments.
+#![cfg(feature = "host")]
+
+use alloy_primitives::{Address, address};
+use alloy_provider::ProviderBuilder;
+use openvm_blobstream::host::deployments::{
+    self, ContractVersion, DEPLOYMENTS, Deployment, LEGACY_DEPLOYMENTS,
+};
+#[cfg(feature = "mock-rpc")]
+use {
+    alloy_primitives::{B256, U256},
+    alloy_rpc_types_eth::Filter,
+    alloy_sol_types::SolCall,
+    openvm_blobstream::{
+        da_oracle::{BlobstreamX, DataCommitmentStored},
+        host::{
+            finality::Finality,
+            mock::{self, EthereumExchange, MockRpc, RpcFixture},
+        },
+    },
+    serde_json::json,
+};
+
+#[test]
+fn deployments_are_found_by_chain_id() {
+    for deployment in DEPLOYMENTS {
+        assert_eq!(
+            deployments::by_chain_id(deployment.chain_id),
+            Some(deployment)
+        );
+    }
+    assert_eq!(
+        deployments::by_chain_id(8453).map(|deployment| deployment.address),
+        Some(address!("0xA83ca7775Bc2889825BcDeDfFa5b758cf69e8794"))
+    );
+    assert_eq!(deployments::by_chain_id(10), None);
+}
+
+#[test]
+fn legacy_deployments_are_the_proxies_before_their_upgrade() {
+    for legacy in LEGACY_DEPLOYMENTS {
+        assert_eq!(legacy.version, ContractVersion::BlobstreamX);
+        let current = deployments::by_chain_id(legacy.chain_id).unwrap();
+        assert_eq!(current.address, legacy.address);
+        assert_eq!(current.previous_versions, [legacy.version]);
+        assert_eq!(
+            deployments::all_by_chain_id(legacy.chain_id).collect::<Vec<_>>(),
+            [current, legacy]
+        );
+    }
+    assert_eq!(deployments::all_by_chain_id(10).count(), 0);
+}
+
+#[test]
+fn blobstream_x_commitments_share_the_event() {
+    assert_eq!(
+        ContractVersion::BlobstreamX.commitment_event(),
+        ContractVersion::Sp1Blobstream.commitment_event()
+    );
+    assert_ne!(
+        ContractVersion::BlobstreamX.commit_selector(),
+        ContractVersion::Sp1Blobstream.commit_selector()
+    );
+    assert_ne!(
+        ContractVersion::QuantumGravityBridge.commitment_event(),
+        ContractVersion::Sp1Blobstream.commitment_event()
+    );
+}
+
+#[test]
+fn only_sp1_blobstream_is_bound() -> eyre::Result<()> {
+    let provider = ProviderBuilder::new().connect_http("http://localhost:8545".parse()?);
+    deployments::ETHEREUM.sp1_blobstream(provider.clone())?;
+
+    let qgb = Deployment {
+        chain: "sepolia",
+        chain_id: 11155111,
+        address: Address::repeat_byte(1),
+        version: ContractVersion::QuantumGravityBridge,
+        previous_versions: &[],
+    };
+    assert!(!qgb.version.is_verifiable());
+    assert!(qgb.sp1_blobstream(provider).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "mock-rpc")]
+#[tokio::test]
+async fn legacy_commitments_are_located_by_version() -> eyre::Result<()> {
+    const HEAD: u64 = 1_000;
+    let blobstream_x = LEGACY_DEPLOYMENTS[0];
+    let (tx_hash, block_hash) = (B256::repeat_byte(0x7c), B256::repeat_byte(0x01));
+    let event = DataCommitmentStored {
+        proofNonce: U256::from(1),
+        startBlock: 100,
+        endBlock: 200,
+        dataCommitment: B256::repeat_byte(0xdc),
+    };
+    let call = BlobstreamX::commitHeaderRangeCall { _targetBlock: 200 }.abi_encode();
+    let filter = Filter::new()
+        .address(blobstream_x.address)
+        .event_signature(ContractVersion::BlobstreamX.commitment_event())
+        .from_block(0)
+        .to_block(HEAD);
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            EthereumExchange::new("eth_blockNumber", json!(format!("{HEAD:#x}"))),
+            EthereumExchange::new(
+                "eth_getLogs",
+                json!([mock::commitment_log(
+                    blobstream_x.address,
+                    &event,
+                    HEAD - 10,
+                    block_hash,
+                    tx_hash
+                )]),
+            )
+            .with_params(json!([filter])),
+            EthereumExchange::new(
+                "eth_getTransactionByHash",
+                mock::transaction(tx_hash, HEAD - 10, block_hash, blobstream_x.address, &call),
+            ),
+        ],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+
+    let (found, found_event) = blobstream_x
+        .find_commit_tx(&provider, 150, Finality::Latest)
+        .await?;
+    assert_eq!((found, found_event.proofNonce), (tx_hash, event.proofNonce));
+    assert_eq!(
+        blobstream_x
+            .find_commit_input(&provider, tx_hash, &event)
+            .await?,
+        call
+    );
+    // the same tx holds no SP1 Blobstream call
+    assert!(
+        deployments::ETHEREUM
+            .find_commit_input(&provider, tx_hash, &event)
+            .await
+            .is_err()
+    );
+
+    let qgb = Deployment {
+        version: ContractVersion::QuantumGravityBridge,
+        ..blobstream_x
+    };
+    let err = qgb
+        .find_commit_tx(&provider, 150, Finality::Latest)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("carry no block range"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Tests of the devnet mode against a mock Celestia devnet of synthetic blocks.
+//!
+//! Run with `cargo test --features devnet,mock-rpc`.
+#![cfg(all(feature = "devnet", feature = "mock-rpc"))]
+
+use alloy_primitives::{B256, U256, hex};
+use alloy_sol_types::SolValue;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use openvm_blobstream::{
+    guest::{self, Backend},
+    host::{
+        MerkleProof,
+        devnet::Devnet,
+        mock::{CelestiaExchange, MockRpc, RpcFixture},
+    },
+    native::{ProofOutputs, shares::Blob},
+    synthetic::{DataCommitment, ExtendedSquare},
+    verifier::{BinaryMerkleProof, Namespace},
+};
+use serde_json::{Value, json};
+
+const TX_HEIGHT: u64 = 13;
+const RANGE_LEN: u64 = 5;
+const TX_HASH: B256 = B256::repeat_byte(0xab);
+
+fn merkle_proof(proof: &BinaryMerkleProof) -> Value {
+    json!({
+        "total": proof.numLeaves.to_string(),
+        "index": proof.key.to_string(),
+        "aunts": proof.sideNodes.iter().map(|node| BASE64.encode(node)).collect::<Vec<_>>(),
+    })
+}
+
+/// A devnet whose block `TX_HEIGHT` holds a blob, the other blocks of the range have arbitrary
+/// data roots.
+fn devnet_fixture() -> RpcFixture {
+    let square = ExtendedSquare::new(&[Blob {
+        namespace: Namespace::new(0, &[0x01; 28]).unwrap(),
+        data: vec![0xab; 2_000],
+    }]);
+    // the attestation of the proof is replaced by the devnet
+    let attestation = DataCommitment::new(TX_HEIGHT, [square.data_root()])
+        .attestation_proof(TX_HEIGHT, U256::ZERO)
+        .unwrap();
+    let proof = square.shares_proof(0, attestation);
+
+    let mut celestia = vec![CelestiaExchange {
+        path: "tx".to_string(),
+        query: [("hash".to_string(), hex::encode_prefixed(TX_HASH))].into(),
+        response: json!({ "result": {
+            "height": TX_HEIGHT.to_string(),
+            "proof": {
+                "data": proof.data.iter().map(|share| BASE64.encode(share)).collect::<Vec<_>>(),
+                "share_proofs": proof.shareProofs.iter().map(|proof| json!({
+                    "start": proof.beginKey.to::<u64>(),
+                    "end": proof.endKey.to::<u64>(),
+                    "nodes": proof
+                        .sideNodes
+                        .iter()
+                        .map(|node| BASE64.encode(node.to_bytes()))
+                        .collect::<Vec<_>>(),
+                })).collect::<Vec<_>>(),
+                "namespace_id": BASE64.encode(proof.namespace.id),
+                "row_proof": {
+                    "row_roots": proof
+                        .rowRoots
+                        .iter()
+                        .map(|root| hex::encode_upper(root.to_bytes()))
+                        .collect::<Vec<_>>(),
+                    "proofs": proof.rowProofs.iter().map(merkle_proof).collect::<Vec<_>>(),
+                },
+                "namespace_version": 0,
+            },
+        }}),
+    }];
+    for height in 11..16 {
+        let data_root = if height == TX_HEIGHT {
+            square.data_root()
+        } else {
+            B256::left_padding_from(&height.to_be_bytes())
+        };
+        celestia.push(CelestiaExchange {
+            path: "block".to_string(),
+            query: [("height".to_string(), height.to_string())].into(),
+            response: json!({ "result": { "block": { "header": {
+                "data_hash": hex::encode_upper(data_root),
+            }}}}),
+        });
+    }
+    RpcFixture {
+        celestia,
+        ethereum: Vec::new(),
+        celestia_node: Vec::new(),
+    }
+}
+
+fn devnet(server: &MockRpc) -> Devnet {
+    Devnet {
+        celestia_rpc_url: server.celestia_url(),
+        range_len: RANGE_LEN,
+        vkey: B256::repeat_byte(0x5a),
+    }
+}
+
+#[tokio::test]
+async fn devnet_commitment_validates() -> eyre::Result<()> {
+    let server = MockRpc::serve(devnet_fixture()).await?;
+    let devnet = devnet(&server);
+    let (event, input) = devnet.build_guest_input(TX_HASH.as_slice()).await?;
+
+    assert_eq!((event.startBlock, event.endBlock), (11, 16));
+    assert_eq!(event.proofNonce, U256::from(3));
+    let outputs = ProofOutputs::abi_decode(&input.commitHeaderRangePublicValues)?;
+    assert_eq!(outputs.dataCommitment, event.dataCommitment);
+    assert_eq!(
+        input.sharesProof.attestationProof.tupleRootNonce,
+        event.proofNonce
+    );
+
+    guest::validate_with(input.clone(), Backend::Both, devnet.sp1_verifier())?;
+    // the mock SP1 proof is only accepted by the mock verifier
+    assert!(guest::validate_with_backend(input, Backend::Evm).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn devnet_commitment_needs_the_whole_range() -> eyre::Result<()> {
+    let mut fixture = devnet_fixture();
+    fixture.celestia.pop();
+    let server = MockRpc::serve(fixture).await?;
+    assert!(
+        devnet(&server)
+            .build_guest_input(TX_HASH.as_slice())
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn commitment_ranges_are_consecutive() {
+    let devnet = Devnet {
+        celestia_rpc_url: String::new(),
+        range_len: RANGE_LEN,
+        vkey: B256::ZERO,
+    };
+    assert!(devnet.commitment_range(0).is_err());
+    assert_eq!(devnet.commitment_range(1).unwrap(), (1..6, U256::from(1)));
+    assert_eq!(devnet.commitment_range(5).unwrap(), (1..6, U256::from(1)));
+    assert_eq!(devnet.commitment_range(6).unwrap(), (6..11, U256::from(2)));
+}
+
+#[test]
+fn oversized_merkle_proofs_are_rejected() {
+    let proof = BinaryMerkleProof {
+        sideNodes: vec![B256::repeat_byte(0x01)],
+        key: U256::from(1),
+        numLeaves: U256::from(2),
+    };
+    let converted = MerkleProof::try_from(proof.clone()).unwrap();
+    assert_eq!((converted.total, converted.index), (2, 1));
+
+    let large = U256::from(u64::MAX) + U256::from(1);
+    for proof in [
+        BinaryMerkleProof {
+            key: large,
+            ..proof.clone()
+        },
+        BinaryMerkleProof {
+            numLeaves: large,
+            ..proof
+        },
+    ] {
+        assert!(MerkleProof::try_from(proof).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+//! The lean execution mode rejects the inputs the mainnet one does, for the same reasons.
+use alloy_primitives::{B256, Bytes, FixedBytes, U256};
+use alloy_sol_types::SolValue;
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, ExecutionMode},
+    verifier::{AttestationProof, BinaryMerkleProof, DataRootTuple, Namespace, SharesProof},
+};
+
+/// An input attesting block `height` with a proof of the blocks `start..end`.
+fn input(start: u64, end: u64, height: u64, proof: &[u8]) -> GuestInput {
+    let shares_proof = SharesProof {
+        data: Vec::new(),
+        shareProofs: Vec::new(),
+        namespace: Namespace {
+            version: FixedBytes::ZERO,
+            id: FixedBytes::ZERO,
+        },
+        rowRoots: Vec::new(),
+        rowProofs: Vec::new(),
+        attestationProof: AttestationProof {
+            tupleRootNonce: U256::ZERO,
+            tuple: DataRootTuple {
+                height: U256::from(height),
+                dataRoot: B256::ZERO,
+            },
+            proof: BinaryMerkleProof {
+                sideNodes: Vec::new(),
+                key: U256::ZERO,
+                numLeaves: U256::ZERO,
+            },
+        },
+    };
+    GuestInput {
+        commitHeaderRangeProof: Bytes::copy_from_slice(proof),
+        commitHeaderRangePublicValues: (B256::ZERO, B256::ZERO, B256::ZERO, start, end, U256::ZERO)
+            .abi_encode_params()
+            .into(),
+        sharesProof: shares_proof,
+    }
+}
+
+#[test]
+fn execution_modes_reject_alike() {
+    let cases = [
+        (
+            input(0, 20_000, 10, &[0; 4]),
+            Some("proof block range too large"),
+        ),
+        (
+            input(10, 20, 5, &[0; 4]),
+            Some("attested block not in range"),
+        ),
+        // underflow of the range length
+        (input(20, 10, 15, &[0; 4]), None),
+        // wrong verifier selector
+        (input(10, 20, 15, &[0xde, 0xad, 0xbe, 0xef]), None),
+        // no selector at all
+        (input(10, 20, 15, &[]), None),
+    ];
+    for (input, reason) in cases {
+        let lean = guest::validate_with_mode(input.clone(), ExecutionMode::Lean).unwrap_err();
+        let mainnet = guest::validate_with_mode(input, ExecutionMode::Mainnet).unwrap_err();
+        assert_eq!(lean.to_string(), mainnet.to_string());
+        if let Some(reason) = reason {
+            assert!(lean.to_string().contains(reason), "{lean}");
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Tests of the Ethereum finality options of the host.
+//!
+//! Run with `cargo test --features mock-rpc`.
+#![cfg(feature = "mock-rpc")]
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_provider::ProviderBuilder;
+use openvm_blobstream::{
+    da_oracle::{DataCommitmentStored, SP1Blobstream},
+    host::{
+        finality::{FinalBlobstream, Finality, Reorged, check_commit_tx},
+        mock::{self, EthereumExchange, MockRpc, RpcFixture},
+        source::EthereumSource,
+    },
+};
+use serde_json::{Value, json};
+
+fn exchange(method: &str, result: Value) -> EthereumExchange {
+    EthereumExchange {
+        method: method.to_string(),
+        params: None,
+        result,
+    }
+}
+
+#[tokio::test]
+async fn confirmations_bound_the_last_block() -> eyre::Result<()> {
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![exchange("eth_blockNumber", json!("0x100"))],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+
+    assert_eq!(Finality::Latest.last_block(&provider).await?, 0x100);
+    assert_eq!(
+        Finality::Confirmations(64).last_block(&provider).await?,
+        0xc0
+    );
+    assert_eq!(
+        Finality::Confirmations(0x200).last_block(&provider).await?,
+        0
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn missing_commit_tx_is_reorged() -> eyre::Result<()> {
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![exchange("eth_getTransactionReceipt", Value::Null)],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+
+    let tx_hash = B256::repeat_byte(1);
+    let block_hash = B256::repeat_byte(2);
+    let err = check_commit_tx(&provider, tx_hash, block_hash)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Reorged>(),
+        Some(&Reorged {
+            tx_hash,
+            block_hash
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn finalized_commitments_are_found() -> eyre::Result<()> {
+    const BLOBSTREAM: Address = Address::repeat_byte(0xb5);
+    let finalized = 0x180;
+    let event = DataCommitmentStored {
+        proofNonce: U256::from(3),
+        startBlock: 100,
+        endBlock: 200,
+        dataCommitment: B256::repeat_byte(0xdc),
+    };
+    let tx_hash = B256::repeat_byte(0xaa);
+
+    // logs are only requested up to the finalized block, not the head
+    let provider = ProviderBuilder::new().connect_http("http://localhost".parse()?);
+    let filter = SP1Blobstream::new(BLOBSTREAM, provider)
+        .DataCommitmentStored_filter()
+        .from_block(0)
+        .to_block(finalized)
+        .filter;
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            exchange("eth_blockNumber", json!("0x200")),
+            exchange(
+                "eth_getBlockByNumber",
+                mock::block(finalized, B256::repeat_byte(0x01)),
+            )
+            .with_params(json!(["finalized", false])),
+            exchange(
+                "eth_getLogs",
+                json!([mock::commitment_log(
+                    BLOBSTREAM,
+                    &event,
+                    finalized - 2,
+                    B256::repeat_byte(0x02),
+                    tx_hash,
+                )]),
+            )
+            .with_params(json!([filter])),
+        ],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+    assert_eq!(Finality::Finalized.last_block(&provider).await?, finalized);
+
+    let source = FinalBlobstream {
+        contract: SP1Blobstream::new(BLOBSTREAM, provider),
+        finality: Finality::Finalized,
+    };
+    let (found_tx_hash, found) = source.find_commit(150).await?;
+    assert_eq!(
+        (found_tx_hash, found.proofNonce),
+        (tx_hash, event.proofNonce)
+    );
+    Ok(())
+}
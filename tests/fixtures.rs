@@ -0,0 +1,167 @@
+//! Golden guest inputs captured from mainnet and Mocha, and systematically mutated variants.
+//!
+//! Every `tests/fixtures/inputs/<name>.json` is a serialized [`GuestInput`] that must be accepted,
+//! they are written along with the RPC fixtures by the `record_fixture` test of `tests/rpc.rs`.
+//! The corpus must hold at least one mainnet input and one Mocha input, named `mainnet-*.json`
+//! and `mocha-*.json`.
+//! Each mutation is applied to every input and must be accepted or rejected with the expected
+//! reason, by both the EVM and the native backends.
+//!
+//! Run with `cargo test --features recorded-fixtures`.
+#![cfg(feature = "recorded-fixtures")]
+use alloy_primitives::{Bytes, U256};
+use alloy_sol_types::SolValue;
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Reverted},
+    native::ProofOutputs,
+};
+use std::path::PathBuf;
+
+/// What the verifier must do with a mutated input.
+#[derive(Debug)]
+enum Expected {
+    Accept,
+    /// Reverted with exactly this reason.
+    Reject(&'static str),
+    /// Reverted with this custom error of the verifier, whatever its arguments.
+    RejectError(&'static str),
+    /// Accepted although it should be rejected, because of the documented gap. The test fails once
+    /// the gap is closed, so the mutation is moved to [`Expected::Reject`].
+    KnownGap(&'static str),
+}
+
+struct Mutation {
+    name: &'static str,
+    apply: fn(&mut GuestInput),
+    expected: Expected,
+}
+
+fn load_inputs() -> Vec<(String, GuestInput)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/inputs");
+    let entries = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("no golden inputs in {}: {e}", dir.display()));
+    let mut inputs = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let input = serde_json::from_slice(&std::fs::read(&path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+            (
+                path.file_stem().unwrap().to_string_lossy().into_owned(),
+                input,
+            )
+        })
+        .collect::<Vec<_>>();
+    inputs.sort_by(|a, b| a.0.cmp(&b.0));
+    for network in ["mainnet", "mocha"] {
+        assert!(
+            inputs.iter().any(|(name, _)| name.starts_with(network)),
+            "no {network} golden input in {}",
+            dir.display()
+        );
+    }
+    inputs
+}
+
+fn public_values(input: &GuestInput) -> ProofOutputs {
+    ProofOutputs::abi_decode(&input.commitHeaderRangePublicValues).unwrap()
+}
+
+fn flip_first_byte(bytes: &mut Bytes) {
+    let mut flipped = bytes.to_vec();
+    flipped[0] ^= 0xff;
+    *bytes = flipped.into();
+}
+
+fn revert_reason(error: &eyre::Report) -> Option<&str> {
+    error
+        .downcast_ref::<Reverted>()
+        .map(|reverted| reverted.reason.as_str())
+}
+
+const MUTATIONS: &[Mutation] = &[
+    Mutation {
+        name: "unchanged",
+        apply: |_| {},
+        expected: Expected::Accept,
+    },
+    Mutation {
+        name: "flipped_share_byte",
+        apply: |input| {
+            let share = input.sharesProof.data.last_mut().unwrap();
+            let mut flipped = share.to_vec();
+            *flipped.last_mut().unwrap() ^= 0x01;
+            *share = flipped.into();
+        },
+        expected: Expected::Reject("invalid sharesProof"),
+    },
+    Mutation {
+        name: "wrong_nonce",
+        apply: |input| input.sharesProof.attestationProof.tupleRootNonce += U256::from(1),
+        expected: Expected::KnownGap(
+            "the verifier checks the attestation against the nonce of the input itself, so the \
+             nonce is not bound to the data commitment of the SP1 proof",
+        ),
+    },
+    Mutation {
+        name: "height_after_range",
+        apply: |input| {
+            let target_block = public_values(input).targetBlock;
+            input.sharesProof.attestationProof.tuple.height = U256::from(target_block + 1);
+        },
+        expected: Expected::Reject("attested block not in range"),
+    },
+    Mutation {
+        name: "height_before_range",
+        apply: |input| {
+            let trusted_block = public_values(input).trustedBlock;
+            input.sharesProof.attestationProof.tuple.height =
+                U256::from(trusted_block) - U256::from(1);
+        },
+        expected: Expected::Reject("attested block not in range"),
+    },
+    // the contract truncates the height to 64 bits before the range check, the attestation proof
+    // still rejects it
+    Mutation {
+        name: "height_above_64_bits",
+        apply: |input| input.sharesProof.attestationProof.tuple.height += U256::from(1) << 64,
+        expected: Expected::Reject("invalid BinaryMerkleTree proof"),
+    },
+    Mutation {
+        name: "tampered_public_values",
+        apply: |input| flip_first_byte(&mut input.commitHeaderRangePublicValues),
+        expected: Expected::Reject("invalid commitHeaderRange proof"),
+    },
+    Mutation {
+        name: "wrong_proof_selector",
+        apply: |input| flip_first_byte(&mut input.commitHeaderRangeProof),
+        expected: Expected::RejectError("WrongVerifierSelector"),
+    },
+];
+
+#[test]
+fn golden_inputs_and_mutations() {
+    for (name, input) in load_inputs() {
+        for mutation in MUTATIONS {
+            let mut mutated = input.clone();
+            (mutation.apply)(&mut mutated);
+            let result = guest::validate_with_backend(mutated, Backend::Both);
+            match (&mutation.expected, result) {
+                (Expected::Accept | Expected::KnownGap(_), Ok(())) => {}
+                (Expected::KnownGap(gap), Err(e)) => panic!(
+                    "{name}/{}: rejected with {e}, the known gap is closed: {gap}",
+                    mutation.name
+                ),
+                (Expected::Reject(reason), Err(e)) if revert_reason(&e) == Some(reason) => {}
+                (Expected::RejectError(error), Err(e))
+                    if revert_reason(&e)
+                        .is_some_and(|reason| reason.starts_with(&format!("{error}("))) => {}
+                (expected, result) => panic!(
+                    "{name}/{}: expected {expected:?}, got {result:?}",
+                    mutation.name
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Tests of the celestia-app gRPC source: the wire format of its messages, and the source against
+//! a mock gRPC server serving a synthetic block.
+//!
+//! The responses are encoded by hand from the proto definitions of celestia-app and CometBFT.
+//!
+//! Run with `cargo test --features grpc,test-utils`.
+#![cfg(all(feature = "grpc", feature = "test-utils"))]
+
+use alloy_primitives::{B256, U256, hex};
+use eyre::OptionExt;
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Sp1Verifier},
+    host::{
+        BlockHeader, TxProof,
+        grpc::{
+            ABCI_QUERY, DATA_ROOT_INCLUSION_PROOF, GET_BLOCK_BY_HEIGHT, GrpcSource, TX_STATUS,
+            proto::{
+                AbciQueryRequest, AbciQueryResponse, BlockIdMessage, BlockMessage,
+                DataRootInclusionProofRequest, DataRootInclusionProofResponse,
+                GetBlockByHeightRequest, GetBlockByHeightResponse, HeaderMessage,
+                MerkleProofMessage, NmtProofMessage, RowProofMessage, ShareProofMessage,
+                TxStatusRequest, TxStatusResponse,
+            },
+        },
+        source::CelestiaSource,
+    },
+    native::shares::Blob,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::{BinaryMerkleProof, Namespace, SharesProof},
+};
+use prost::Message;
+use std::{
+    convert::Infallible,
+    future::{Ready, ready},
+    marker::PhantomData,
+    net::SocketAddr,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tonic::{
+    Status,
+    body::Body,
+    codec::ProstCodec,
+    codegen::{BoxFuture, Service, http},
+    server::{Grpc, NamedService, UnaryService},
+    transport::{Server, server::TcpIncoming},
+};
+
+const HEIGHT: u64 = 42;
+const TX_INDEX: u32 = 3;
+const TX_HASH: B256 = B256::repeat_byte(0xaa);
+const BLOCK_HASH: B256 = B256::repeat_byte(0xbb);
+
+fn decodes_to<M: Message + Default + PartialEq>(bytes: &[u8], message: &M) {
+    assert_eq!(&M::decode(bytes).unwrap(), message);
+    assert_eq!(message.encode_to_vec(), bytes);
+}
+
+#[test]
+fn tx_status_round_trips() {
+    let bytes = [&hex!("08051002 2a09")[..], b"COMMITTED"].concat();
+    decodes_to(
+        &bytes,
+        &TxStatusResponse {
+            height: 5,
+            index: 2,
+            execution_code: 0,
+            error: String::new(),
+            status: "COMMITTED".to_string(),
+        },
+    );
+    let hash = hex::encode_upper(TX_HASH);
+    let bytes = [&hex!("0a40")[..], hash.as_bytes()].concat();
+    decodes_to(&bytes, &TxStatusRequest { tx_id: hash });
+}
+
+#[test]
+fn block_round_trips() {
+    let bytes = [
+        &hex!("0a22 0a20")[..],
+        BLOCK_HASH.as_slice(),
+        &hex!("1a26 0a24 182a 3a20"),
+        &[0xdd; 32],
+    ]
+    .concat();
+    let response = GetBlockByHeightResponse {
+        block_id: Some(BlockIdMessage {
+            hash: BLOCK_HASH.to_vec(),
+        }),
+        block: None,
+        sdk_block: Some(BlockMessage {
+            header: Some(HeaderMessage {
+                height: 42,
+                data_hash: vec![0xdd; 32],
+            }),
+        }),
+    };
+    decodes_to(&bytes, &response);
+    decodes_to(&hex!("082a"), &GetBlockByHeightRequest { height: 42 });
+
+    // the fields the host does not read, here the chain id of the header, are skipped
+    let bytes = [
+        &hex!("0a22 0a20")[..],
+        BLOCK_HASH.as_slice(),
+        &hex!("1a2f 0a2d 1207"),
+        b"mocha-4",
+        &hex!("182a 3a20"),
+        &[0xdd; 32],
+    ]
+    .concat();
+    assert_eq!(
+        GetBlockByHeightResponse::decode(bytes.as_slice()).unwrap(),
+        response
+    );
+}
+
+#[test]
+fn data_root_inclusion_proof_round_trips() {
+    let bytes = [
+        &hex!("0a48 0804 1001 2220")[..],
+        &[0x01; 32],
+        &hex!("2220"),
+        &[0x02; 32],
+    ]
+    .concat();
+    decodes_to(
+        &bytes,
+        &DataRootInclusionProofResponse {
+            proof: Some(MerkleProofMessage {
+                total: 4,
+                index: 1,
+                aunts: vec![vec![0x01; 32], vec![0x02; 32]],
+            }),
+        },
+    );
+    decodes_to(
+        &hex!("082a 1029 182c"),
+        &DataRootInclusionProofRequest {
+            height: 42,
+            start: 41,
+            end: 44,
+        },
+    );
+}
+
+#[test]
+fn share_proof_round_trips() -> eyre::Result<()> {
+    let proof = hex!(
+        "0a02 5151"
+        "1209 0801 1002 1a03 616161"
+        "1a03 000007"
+        "2207 0a01ff 1202 0801"
+    );
+    let message = ShareProofMessage {
+        data: vec![vec![0x51; 2]],
+        share_proofs: vec![NmtProofMessage {
+            start: 1,
+            end: 2,
+            nodes: vec![b"aaa".to_vec()],
+        }],
+        namespace_id: vec![0, 0, 7],
+        row_proof: Some(RowProofMessage {
+            row_roots: vec![vec![0xff]],
+            proofs: vec![MerkleProofMessage {
+                total: 1,
+                index: 0,
+                aunts: Vec::new(),
+            }],
+        }),
+        namespace_version: 0,
+    };
+    decodes_to(&proof, &message);
+
+    let path = format!("custom/txInclusionProof/{TX_INDEX}");
+    let bytes = [&hex!("1219")[..], path.as_bytes(), &hex!("182a")].concat();
+    decodes_to(
+        &bytes,
+        &AbciQueryRequest {
+            data: Vec::new(),
+            path,
+            height: 42,
+            prove: false,
+        },
+    );
+    let bytes = [&hex!("3a1d")[..], &proof].concat();
+    let response = AbciQueryResponse::decode(bytes.as_slice())?;
+    assert_eq!(response.code, 0);
+    assert_eq!(
+        ShareProofMessage::decode(response.value.as_slice())?,
+        message
+    );
+
+    let proof = TxProof::try_from(message)?;
+    assert_eq!(proof.data, [[0x51; 2]]);
+    assert_eq!(
+        (proof.share_proofs[0].start, proof.share_proofs[0].end),
+        (1, 2)
+    );
+    assert_eq!(proof.row_proof.proofs[0].total, 1);
+    Ok(())
+}
+
+/// The responses of a celestia-app node for a block holding the tx `TX_HASH`.
+struct App {
+    status: String,
+    tx_proof: ShareProofMessage,
+    data_hash: B256,
+    inclusion_proof: MerkleProofMessage,
+    range: (u64, u64),
+}
+
+impl App {
+    fn tx_status(&self, request: TxStatusRequest) -> Result<TxStatusResponse, Box<Status>> {
+        if request.tx_id != hex::encode_upper(TX_HASH) {
+            return Ok(TxStatusResponse {
+                status: "UNKNOWN".to_string(),
+                ..Default::default()
+            });
+        }
+        Ok(TxStatusResponse {
+            height: HEIGHT as i64,
+            index: TX_INDEX,
+            execution_code: 0,
+            error: String::new(),
+            status: self.status.clone(),
+        })
+    }
+
+    fn abci_query(&self, request: AbciQueryRequest) -> Result<AbciQueryResponse, Box<Status>> {
+        if request.path != format!("custom/txInclusionProof/{TX_INDEX}")
+            || request.height != HEIGHT as i64
+        {
+            return Ok(AbciQueryResponse {
+                code: 1,
+                log: format!("no tx at {} in block {}", request.path, request.height),
+                value: Vec::new(),
+            });
+        }
+        Ok(AbciQueryResponse {
+            code: 0,
+            log: String::new(),
+            value: self.tx_proof.encode_to_vec(),
+        })
+    }
+
+    fn get_block_by_height(
+        &self,
+        request: GetBlockByHeightRequest,
+    ) -> Result<GetBlockByHeightResponse, Box<Status>> {
+        if request.height != HEIGHT as i64 {
+            return Err(Status::not_found(format!("block {}", request.height)).into());
+        }
+        Ok(GetBlockByHeightResponse {
+            block_id: Some(BlockIdMessage {
+                hash: BLOCK_HASH.to_vec(),
+            }),
+            block: None,
+            sdk_block: Some(BlockMessage {
+                header: Some(HeaderMessage {
+                    height: request.height,
+                    data_hash: self.data_hash.to_vec(),
+                }),
+            }),
+        })
+    }
+
+    fn data_root_inclusion_proof(
+        &self,
+        request: DataRootInclusionProofRequest,
+    ) -> Result<DataRootInclusionProofResponse, Box<Status>> {
+        if request.height != HEIGHT as i64 || (request.start, request.end) != self.range {
+            return Err(Status::invalid_argument("no such data commitment").into());
+        }
+        Ok(DataRootInclusionProofResponse {
+            proof: Some(self.inclusion_proof.clone()),
+        })
+    }
+}
+
+/// Answers a unary call with `F`, whose errors are boxed as they are large.
+struct Handler<F>(F);
+
+impl<Req, Res, F: FnMut(Req) -> Result<Res, Box<Status>>> UnaryService<Req> for Handler<F> {
+    type Response = Res;
+    type Future = Ready<Result<tonic::Response<Res>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+        ready(
+            (self.0)(request.into_inner())
+                .map(tonic::Response::new)
+                .map_err(|status| *status),
+        )
+    }
+}
+
+async fn unary<Req, Res>(
+    request: http::Request<Body>,
+    handler: impl FnMut(Req) -> Result<Res, Box<Status>>,
+) -> http::Response<Body>
+where
+    Req: Message + Default + Send + 'static,
+    Res: Message + Send + 'static,
+{
+    Grpc::new(ProstCodec::<Res, Req>::default())
+        .unary(Handler(handler), request)
+        .await
+}
+
+/// A gRPC service `N` of [`App`].
+struct AppService<N> {
+    app: Arc<App>,
+    name: PhantomData<N>,
+}
+
+impl<N> Clone for AppService<N> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            name: PhantomData,
+        }
+    }
+}
+
+struct TxService;
+struct TendermintService;
+struct BlobstreamService;
+
+impl NamedService for AppService<TxService> {
+    const NAME: &'static str = "celestia.core.v1.tx.Tx";
+}
+
+impl NamedService for AppService<TendermintService> {
+    const NAME: &'static str = "cosmos.base.tendermint.v1beta1.Service";
+}
+
+impl NamedService for AppService<BlobstreamService> {
+    const NAME: &'static str = "tendermint.rpc.grpc.BlobstreamAPI";
+}
+
+impl<N> Service<http::Request<Body>> for AppService<N> {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let app = self.app.clone();
+        Box::pin(async move {
+            Ok(match request.uri().path() {
+                TX_STATUS => unary(request, |request| app.tx_status(request)).await,
+                ABCI_QUERY => unary(request, |request| app.abci_query(request)).await,
+                GET_BLOCK_BY_HEIGHT => {
+                    unary(request, |request| app.get_block_by_height(request)).await
+                }
+                DATA_ROOT_INCLUSION_PROOF => {
+                    unary(request, |request| app.data_root_inclusion_proof(request)).await
+                }
+                path => Status::unimplemented(path.to_string()).into_http(),
+            })
+        })
+    }
+}
+
+async fn serve(app: App) -> eyre::Result<SocketAddr> {
+    let app = Arc::new(app);
+    let incoming = TcpIncoming::bind(SocketAddr::from(([127, 0, 0, 1], 0)))?;
+    let addr = incoming.local_addr()?;
+    let router = Server::builder()
+        .add_service(AppService::<TxService> {
+            app: app.clone(),
+            name: PhantomData,
+        })
+        .add_service(AppService::<TendermintService> {
+            app: app.clone(),
+            name: PhantomData,
+        })
+        .add_service(AppService::<BlobstreamService> {
+            app,
+            name: PhantomData,
+        });
+    tokio::spawn(router.serve_with_incoming(incoming));
+    Ok(addr)
+}
+
+/// A node whose block `HEIGHT` holds the tx `TX_HASH`, as the second of two blobs, committed in a
+/// range of 3 blocks.
+fn app(status: &str) -> eyre::Result<(App, DataCommitment)> {
+    let blobs = [
+        Blob {
+            namespace: Namespace::new(0, &[0x00; 28])?,
+            data: vec![0x11; 700],
+        },
+        Blob {
+            namespace: Namespace::new(0, &[0x07; 28])?,
+            data: vec![0x22; 3_000],
+        },
+    ];
+    let square = ExtendedSquare::new(&blobs);
+    let commitment = DataCommitment::new(
+        HEIGHT - 1,
+        [
+            B256::repeat_byte(0x01),
+            square.data_root(),
+            B256::repeat_byte(0x02),
+        ],
+    );
+    let attestation = commitment
+        .attestation_proof(HEIGHT, U256::from(1))
+        .ok_or_eyre("height outside of the commitment")?;
+    let proof = square.shares_proof(1, attestation.clone());
+    let merkle_proof = |proof: &BinaryMerkleProof| MerkleProofMessage {
+        total: proof.numLeaves.to(),
+        index: proof.key.to(),
+        aunts: proof.sideNodes.iter().map(|node| node.to_vec()).collect(),
+    };
+    let tx_proof = ShareProofMessage {
+        data: proof.data.iter().map(|share| share.to_vec()).collect(),
+        share_proofs: proof
+            .shareProofs
+            .iter()
+            .map(|proof| NmtProofMessage {
+                start: proof.beginKey.to(),
+                end: proof.endKey.to(),
+                nodes: proof
+                    .sideNodes
+                    .iter()
+                    .map(|node| node.to_bytes().to_vec())
+                    .collect(),
+            })
+            .collect(),
+        namespace_id: proof.namespace.id.to_vec(),
+        row_proof: Some(RowProofMessage {
+            row_roots: proof
+                .rowRoots
+                .iter()
+                .map(|root| root.to_bytes().to_vec())
+                .collect(),
+            proofs: proof.rowProofs.iter().map(merkle_proof).collect(),
+        }),
+        namespace_version: proof.namespace.version[0].into(),
+    };
+    let app = App {
+        status: status.to_string(),
+        tx_proof,
+        data_hash: square.data_root(),
+        inclusion_proof: merkle_proof(&attestation.proof),
+        range: (commitment.start_block, commitment.end_block()),
+    };
+    Ok((app, commitment))
+}
+
+#[tokio::test]
+async fn grpc_proofs_validate() -> eyre::Result<()> {
+    let (app, commitment) = app("COMMITTED")?;
+    let data_hash = app.data_hash;
+    let addr = serve(app).await?;
+    let source = GrpcSource::connect(&format!("http://{addr}")).await?;
+
+    let tx = source.get_tx(TX_HASH).await?;
+    assert_eq!(tx.height, HEIGHT);
+    assert_eq!(
+        source.get_header(HEIGHT).await?,
+        BlockHeader {
+            height: HEIGHT,
+            hash: BLOCK_HASH,
+            data_hash,
+        }
+    );
+    let inclusion_proof = source
+        .get_data_root_inclusion_proof(HEIGHT, commitment.start_block, commitment.end_block())
+        .await?;
+    let input = GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof()?,
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: SharesProof::new(
+            HEIGHT,
+            source.get_block_data_hash(HEIGHT).await?,
+            U256::from(1),
+            inclusion_proof,
+            tx.proof,
+        )?,
+    };
+    guest::validate_with(input, Backend::Both, Sp1Verifier::Stub)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn grpc_errors_are_returned() -> eyre::Result<()> {
+    let (app, _) = app("PENDING")?;
+    let addr = serve(app).await?;
+    let source = GrpcSource::connect(&format!("http://{addr}")).await?;
+
+    let err = source.get_tx(TX_HASH).await.unwrap_err();
+    assert_eq!(err.to_string(), format!("tx {TX_HASH} is PENDING"));
+    let err = source.get_tx_proof(HEIGHT, TX_INDEX + 1).await.unwrap_err();
+    assert!(
+        err.to_string().contains("txInclusionProof query failed"),
+        "{err}"
+    );
+    let err = source.get_header(HEIGHT + 1).await.unwrap_err();
+    assert!(err.to_string().contains("block 43"), "{err}");
+    Ok(())
+}
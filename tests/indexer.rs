@@ -0,0 +1,225 @@
+//! Tests of the commitment index of the host.
+//!
+//! Run with `cargo test --features host`, and `mock-rpc` for the sync against a mock RPC.
+#![cfg(feature = "host")]
+
+use alloy_primitives::{B256, U256};
+use openvm_blobstream::host::indexer::{CommitmentIndex, IndexedCommitment};
+#[cfg(feature = "mock-rpc")]
+use {
+    alloy_primitives::Address,
+    alloy_provider::ProviderBuilder,
+    openvm_blobstream::{
+        da_oracle::SP1Blobstream,
+        host::{
+            finality::Reorged,
+            indexer::Indexer,
+            mock::{self, EthereumExchange, MockRpc, RpcFixture},
+            source::EthereumSource,
+        },
+    },
+    serde_json::json,
+};
+
+fn commitment(nonce: u64, start_block: u64, end_block: u64) -> IndexedCommitment {
+    IndexedCommitment {
+        proof_nonce: U256::from(nonce),
+        start_block,
+        end_block,
+        data_commitment: B256::repeat_byte(nonce as u8),
+        eth_tx_hash: B256::repeat_byte(0x80 | nonce as u8),
+        eth_block_number: 1000 + nonce,
+        eth_block_hash: B256::repeat_byte(0x01),
+    }
+}
+
+#[test]
+fn covering_commitment_is_found() {
+    let mut index = CommitmentIndex::new(0);
+    // inserted out of order, and once twice
+    for (nonce, start, end) in [(3, 200, 300), (1, 1, 100), (2, 100, 200), (3, 200, 300)] {
+        index.insert(commitment(nonce, start, end));
+    }
+    assert_eq!(index.commitments().len(), 3);
+
+    let nonce = |height| {
+        index
+            .covering(height)
+            .map(|commitment| commitment.proof_nonce.to::<u64>())
+    };
+    assert_eq!(nonce(0), None);
+    assert_eq!(nonce(1), Some(1));
+    assert_eq!(nonce(99), Some(1));
+    assert_eq!(nonce(100), Some(2));
+    assert_eq!(nonce(250), Some(3));
+    assert_eq!(nonce(299), Some(3));
+    assert_eq!(nonce(300), None);
+}
+
+#[test]
+fn index_is_persisted() -> eyre::Result<()> {
+    let path = std::env::temp_dir()
+        .join(format!("blobstream-index-{}", std::process::id()))
+        .join("index.json");
+    let _ = std::fs::remove_file(&path);
+
+    let mut index = CommitmentIndex::load_or_new(&path, 42)?;
+    assert_eq!(index.next_eth_block, 42);
+    index.insert(commitment(1, 1, 100));
+    index.next_eth_block = 1001;
+    index.save(&path)?;
+
+    let loaded = CommitmentIndex::load_or_new(&path, 42)?;
+    assert_eq!(loaded.next_eth_block, 1001);
+    assert_eq!(loaded.commitments(), index.commitments());
+    Ok(())
+}
+
+#[cfg(feature = "mock-rpc")]
+#[tokio::test]
+async fn logs_are_synced_in_batches() -> eyre::Result<()> {
+    const BLOBSTREAM: Address = Address::repeat_byte(0xb5);
+    const START: u64 = 1_000;
+    let path = std::env::temp_dir()
+        .join(format!("blobstream-index-sync-{}", std::process::id()))
+        .join("index.json");
+    let _ = std::fs::remove_file(&path);
+
+    // the logs of blocks [START, START + 99] then [START + 100, START + 150], the last
+    // confirmed block, where the commitment of nonce 2 is delivered by both batches
+    let provider = ProviderBuilder::new().connect_http("http://localhost".parse()?);
+    let contract = SP1Blobstream::new(BLOBSTREAM, provider);
+    let log = |nonce: u64, start_block: u64, end_block: u64, eth_block: u64| {
+        let commitment = commitment(nonce, start_block, end_block);
+        mock::commitment_log(
+            BLOBSTREAM,
+            &commitment.event(),
+            eth_block,
+            B256::repeat_byte(0x01),
+            commitment.eth_tx_hash,
+        )
+    };
+    let get_logs = |from: u64, to: u64, logs: serde_json::Value| {
+        let filter = contract
+            .DataCommitmentStored_filter()
+            .from_block(from)
+            .to_block(to)
+            .filter;
+        EthereumExchange::new("eth_getLogs", logs).with_params(json!([filter]))
+    };
+    let head = START + 150 + 64;
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            EthereumExchange::new("eth_blockNumber", json!(format!("{head:#x}"))),
+            EthereumExchange::new(
+                "eth_getTransactionReceipt",
+                mock::receipt(B256::repeat_byte(0x82), START + 99, B256::repeat_byte(0x01)),
+            ),
+            get_logs(
+                START,
+                START + 99,
+                json!([log(1, 1, 100, START + 10), log(2, 100, 200, START + 99)]),
+            ),
+            get_logs(
+                START + 100,
+                START + 150,
+                json!([log(2, 100, 200, START + 99), log(3, 200, 300, START + 150)]),
+            ),
+        ],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+    let mut indexer = Indexer::open(SP1Blobstream::new(BLOBSTREAM, provider), &path, START)?;
+    indexer.batch_size = 100;
+
+    assert_eq!(indexer.sync().await?, 3);
+    assert_eq!(
+        indexer.index().read().unwrap().commitments(),
+        [
+            IndexedCommitment {
+                eth_block_number: START + 10,
+                ..commitment(1, 1, 100)
+            },
+            IndexedCommitment {
+                eth_block_number: START + 99,
+                ..commitment(2, 100, 200)
+            },
+            IndexedCommitment {
+                eth_block_number: START + 150,
+                ..commitment(3, 200, 300)
+            },
+        ]
+    );
+    let (tx_hash, event) = indexer.find_commit(150).await?;
+    assert_eq!(
+        (tx_hash, event.proofNonce),
+        (B256::repeat_byte(0x82), U256::from(2))
+    );
+    assert!(indexer.find_commit(300).await.is_err());
+
+    // nothing new below the head, the index is reloaded where it stopped
+    assert_eq!(indexer.sync().await?, 0);
+    let reopened = CommitmentIndex::load_or_new(&path, START)?;
+    assert_eq!(reopened.next_eth_block, START + 151);
+    assert_eq!(reopened.commitments().len(), 3);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(feature = "mock-rpc")]
+#[tokio::test]
+async fn reorganized_commitments_are_dropped() -> eyre::Result<()> {
+    const BLOBSTREAM: Address = Address::repeat_byte(0xb5);
+    const START: u64 = 1_000;
+    let path = std::env::temp_dir()
+        .join(format!("blobstream-index-reorg-{}", std::process::id()))
+        .join("index.json");
+    let _ = std::fs::remove_file(&path);
+
+    // the commit tx of block `START + 10` was moved to another block since it was indexed
+    let indexed = commitment(1, 1, 100);
+    let server = MockRpc::serve(RpcFixture {
+        celestia: Vec::new(),
+        ethereum: vec![
+            EthereumExchange::new("eth_blockNumber", json!(format!("{:#x}", START + 100))),
+            EthereumExchange::new(
+                "eth_getLogs",
+                json!([mock::commitment_log(
+                    BLOBSTREAM,
+                    &indexed.event(),
+                    START + 10,
+                    indexed.eth_block_hash,
+                    indexed.eth_tx_hash,
+                )]),
+            ),
+            EthereumExchange::new(
+                "eth_getTransactionReceipt",
+                mock::receipt(indexed.eth_tx_hash, START + 12, B256::repeat_byte(0x02)),
+            ),
+        ],
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+    let indexer = Indexer::open(SP1Blobstream::new(BLOBSTREAM, provider), &path, START)?;
+    assert_eq!(indexer.sync().await?, 1);
+
+    let err = indexer.find_commit(50).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Reorged>(),
+        Some(&Reorged {
+            tx_hash: indexed.eth_tx_hash,
+            block_hash: indexed.eth_block_hash,
+        })
+    );
+    assert_eq!(indexer.covering(50), None);
+    let reopened = CommitmentIndex::load_or_new(&path, START)?;
+    assert_eq!(reopened.next_eth_block, START + 10);
+    assert!(reopened.commitments().is_empty());
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
@@ -0,0 +1,306 @@
+//! Tests of the light client verification against a mock CometBFT RPC serving a signed chain.
+//!
+//! Run with `cargo test --features light-client,mock-rpc,test-utils`.
+#![cfg(all(feature = "light-client", feature = "mock-rpc", feature = "test-utils"))]
+
+use alloy_primitives::{B256, U256};
+use ed25519_consensus::SigningKey;
+use openvm_blobstream::{
+    GuestInput,
+    host::{
+        light_client::{LightClientOptions, TrustRoot, check_attested_header, verify_header},
+        mock::{CelestiaExchange, MockRpc, RpcFixture},
+    },
+    native::shares::Blob,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::Namespace,
+};
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tendermint::{
+    Hash, PublicKey, Signature, Time, account,
+    block::{self, CommitSig, Header, Round, header::Version, signed_header::SignedHeader},
+    hash::AppHash,
+    validator,
+    vote::{self, ValidatorIndex, Vote},
+};
+
+const CHAIN_ID: &str = "mocha-4";
+const TRUSTED_HEIGHT: u64 = 1;
+/// The last height signed by the first validator set, the next ones are signed by a disjoint set.
+const LAST_OLD_HEIGHT: u64 = 5;
+const TARGET_HEIGHT: u64 = 10;
+
+fn validator_set(keys: &[SigningKey]) -> validator::Set {
+    validator::Set::without_proposer(
+        keys.iter()
+            .map(|key| {
+                let public_key =
+                    PublicKey::from_raw_ed25519(&key.verification_key().to_bytes()).unwrap();
+                validator::Info::new(public_key, 10u32.into())
+            })
+            .collect(),
+    )
+}
+
+/// A chain whose validators all sign every block, and are replaced after `LAST_OLD_HEIGHT`.
+struct Chain {
+    old: Vec<SigningKey>,
+    new: Vec<SigningKey>,
+    /// The data hash of block `TARGET_HEIGHT`.
+    data_hash: B256,
+    start: u64,
+}
+
+impl Chain {
+    fn new(data_hash: B256) -> Self {
+        let keys = |seed: u8| (0..4).map(|i| SigningKey::from([seed + i; 32])).collect();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Self {
+            old: keys(1),
+            new: keys(11),
+            data_hash,
+            start: now.as_secs() - 3_600,
+        }
+    }
+
+    fn keys(&self, height: u64) -> &[SigningKey] {
+        if height <= LAST_OLD_HEIGHT {
+            &self.old
+        } else {
+            &self.new
+        }
+    }
+
+    fn signed_header(&self, height: u64) -> eyre::Result<SignedHeader> {
+        let validators = validator_set(self.keys(height));
+        let time = Time::from_unix_timestamp((self.start + height) as i64, 0)?;
+        let data_hash = match height {
+            TARGET_HEIGHT => self.data_hash,
+            _ => B256::repeat_byte(height as u8),
+        };
+        let header = Header {
+            version: Version { block: 11, app: 1 },
+            chain_id: CHAIN_ID.parse()?,
+            height: height.try_into()?,
+            time,
+            last_block_id: None,
+            last_commit_hash: None,
+            data_hash: Some(Hash::Sha256(data_hash.0)),
+            validators_hash: validators.hash(),
+            next_validators_hash: validator_set(self.keys(height + 1)).hash(),
+            consensus_hash: Hash::Sha256([0; 32]),
+            app_hash: AppHash::default(),
+            last_results_hash: None,
+            evidence_hash: None,
+            proposer_address: validators.validators()[0].address,
+        };
+        let block_id = block::Id {
+            hash: header.hash(),
+            part_set_header: block::parts::Header::new(1, Hash::Sha256([0x01; 32]))?,
+        };
+        let signatures = validators
+            .validators()
+            .iter()
+            .map(|validator| {
+                let key = self
+                    .keys(height)
+                    .iter()
+                    .find(|key| {
+                        let public_key =
+                            PublicKey::from_raw_ed25519(&key.verification_key().to_bytes());
+                        public_key.map(account::Id::from) == Some(validator.address)
+                    })
+                    .unwrap();
+                let vote = Vote {
+                    vote_type: vote::Type::Precommit,
+                    height: header.height,
+                    round: Round::default(),
+                    block_id: Some(block_id),
+                    timestamp: Some(time),
+                    validator_address: validator.address,
+                    validator_index: ValidatorIndex::try_from(0u32)?,
+                    signature: None,
+                    extension: Vec::new(),
+                    extension_signature: None,
+                };
+                let sign_bytes = vote.into_signable_vec(header.chain_id.clone());
+                Ok(CommitSig::BlockIdFlagCommit {
+                    validator_address: validator.address,
+                    timestamp: time,
+                    signature: Signature::new(key.sign(&sign_bytes).to_bytes())?,
+                })
+            })
+            .collect::<eyre::Result<_>>()?;
+        let commit = block::Commit {
+            height: header.height,
+            round: Round::default(),
+            block_id,
+            signatures,
+        };
+        Ok(SignedHeader::new(header, commit)?)
+    }
+
+    fn hash(&self, height: u64) -> eyre::Result<B256> {
+        Ok(B256::from_slice(
+            self.signed_header(height)?.header.hash().as_bytes(),
+        ))
+    }
+
+    /// The `/commit` and `/validators` responses of every block up to the one after `TARGET_HEIGHT`.
+    fn fixture(&self) -> eyre::Result<RpcFixture> {
+        let mut celestia = Vec::new();
+        for height in 1..=TARGET_HEIGHT + 1 {
+            let query = [("height".to_string(), height.to_string())];
+            let validators = validator_set(self.keys(height));
+            celestia.push(CelestiaExchange {
+                path: "commit".to_string(),
+                query: query.clone().into(),
+                response: json!({ "result": {
+                    "signed_header": self.signed_header(height)?,
+                    "canonical": true,
+                }}),
+            });
+            celestia.push(CelestiaExchange {
+                path: "validators".to_string(),
+                query: query.into(),
+                response: json!({ "result": {
+                    "block_height": height.to_string(),
+                    "validators": validators.validators(),
+                    "count": validators.validators().len().to_string(),
+                    "total": validators.validators().len().to_string(),
+                }}),
+            });
+        }
+        Ok(RpcFixture {
+            celestia,
+            ethereum: Vec::new(),
+            celestia_node: Vec::new(),
+        })
+    }
+}
+
+/// An input attesting a blob of block `TARGET_HEIGHT`.
+fn synthetic_input() -> GuestInput {
+    let square = ExtendedSquare::new(&[Blob {
+        namespace: Namespace::new(0, &[0x03; 28]).unwrap(),
+        data: vec![0x5c; 1_500],
+    }]);
+    let commitment = DataCommitment::new(TARGET_HEIGHT, [square.data_root()]);
+    let attestation = commitment
+        .attestation_proof(TARGET_HEIGHT, U256::from(1))
+        .unwrap();
+    GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof().unwrap(),
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: square.shares_proof(0, attestation),
+    }
+}
+
+#[tokio::test]
+async fn headers_are_verified_from_the_trust_root() -> eyre::Result<()> {
+    let chain = Chain::new(B256::repeat_byte(0xdd));
+    let server = MockRpc::serve(chain.fixture()?).await?;
+    let trust_root = TrustRoot {
+        height: TRUSTED_HEIGHT,
+        hash: chain.hash(TRUSTED_HEIGHT)?,
+    };
+    let options = LightClientOptions::default();
+
+    // signed by the trusted validators, in a single step
+    let header = verify_header(&server.celestia_url(), trust_root, 4, &options).await?;
+    assert_eq!(header.hash, chain.hash(4)?);
+    assert_eq!(header.data_hash, B256::repeat_byte(4));
+
+    let header =
+        verify_header(&server.celestia_url(), trust_root, TRUSTED_HEIGHT, &options).await?;
+    assert_eq!(header.hash, trust_root.hash);
+    Ok(())
+}
+
+#[tokio::test]
+async fn validator_set_changes_are_bisected() -> eyre::Result<()> {
+    let chain = Chain::new(B256::repeat_byte(0xdd));
+    let server = MockRpc::serve(chain.fixture()?).await?;
+    let trust_root = TrustRoot {
+        height: TRUSTED_HEIGHT,
+        hash: chain.hash(TRUSTED_HEIGHT)?,
+    };
+
+    // none of the trusted validators signs the target, which is only reached through block
+    // `LAST_OLD_HEIGHT`, the last one signed by them
+    let header = verify_header(
+        &server.celestia_url(),
+        trust_root,
+        TARGET_HEIGHT,
+        &LightClientOptions::default(),
+    )
+    .await?;
+    assert_eq!(header.height, TARGET_HEIGHT);
+    assert_eq!(header.hash, chain.hash(TARGET_HEIGHT)?);
+    assert_eq!(header.data_hash, B256::repeat_byte(0xdd));
+    Ok(())
+}
+
+#[tokio::test]
+async fn wrong_trusted_hash_is_rejected() -> eyre::Result<()> {
+    let chain = Chain::new(B256::repeat_byte(0xdd));
+    let server = MockRpc::serve(chain.fixture()?).await?;
+    let trust_root = TrustRoot {
+        height: TRUSTED_HEIGHT,
+        hash: B256::repeat_byte(0xee),
+    };
+    let err = verify_header(
+        &server.celestia_url(),
+        trust_root,
+        TARGET_HEIGHT,
+        &LightClientOptions::default(),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "header {TRUSTED_HEIGHT} has hash {}, expected {}",
+            chain.hash(TRUSTED_HEIGHT)?,
+            trust_root.hash
+        )
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn diverging_data_root_is_flagged() -> eyre::Result<()> {
+    let mut input = synthetic_input();
+    let data_root = input.sharesProof.attestationProof.tuple.dataRoot;
+    let chain = Chain::new(data_root);
+    let server = MockRpc::serve(chain.fixture()?).await?;
+    let trust_root = TrustRoot {
+        height: TRUSTED_HEIGHT,
+        hash: chain.hash(TRUSTED_HEIGHT)?,
+    };
+    let header = verify_header(
+        &server.celestia_url(),
+        trust_root,
+        TARGET_HEIGHT,
+        &LightClientOptions::default(),
+    )
+    .await?;
+    check_attested_header(&input, &header)?;
+
+    input.sharesProof.attestationProof.tuple.dataRoot = B256::repeat_byte(0xee);
+    let err = check_attested_header(&input, &header).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "input attests data root {} of celestia block {TARGET_HEIGHT}, verified header has \
+             {data_root}",
+            B256::repeat_byte(0xee)
+        )
+    );
+
+    input.sharesProof.attestationProof.tuple.height = U256::from(TARGET_HEIGHT + 1);
+    let err = check_attested_header(&input, &header).unwrap_err();
+    assert!(err.to_string().contains("verified header is"), "{err}");
+    Ok(())
+}
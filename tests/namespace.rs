@@ -0,0 +1,198 @@
+//! Tests of the namespace proofs of synthetic squares, complete and absent namespaces.
+//!
+//! Run with `cargo test --features test-utils`.
+#![cfg(feature = "test-utils")]
+use alloy_primitives::{B256, Bytes, U256, keccak256};
+use alloy_sol_types::{SolCall, SolValue};
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Sp1Verifier},
+    native::{
+        self,
+        namespace::{NamespaceOutputs, NamespaceProof},
+        shares::Blob,
+    },
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::Namespace,
+};
+
+const HEIGHT: u64 = 100;
+
+fn namespace(id: u8) -> Namespace {
+    Namespace::new(0, &[[0u8; 18].as_slice(), &[id; 10]].concat()).unwrap()
+}
+
+/// A square with two blobs of namespace 0x20 spanning several rows, between blobs of 0x10 and
+/// 0x30.
+fn square() -> (ExtendedSquare, Vec<Blob>) {
+    let blobs = vec![
+        Blob {
+            namespace: namespace(0x10),
+            data: vec![0x11; 5_000],
+        },
+        Blob {
+            namespace: namespace(0x20),
+            data: vec![0x22; 5_000],
+        },
+        Blob {
+            namespace: namespace(0x20),
+            data: vec![0x23; 1_500],
+        },
+        Blob {
+            namespace: namespace(0x30),
+            data: vec![0x33; 700],
+        },
+    ];
+    (ExtendedSquare::new(&blobs), blobs)
+}
+
+fn verifies(square: &ExtendedSquare, proof: &NamespaceProof) -> bool {
+    native::namespace::verify(proof, &square.data_root())
+}
+
+#[test]
+fn complete_namespace_verifies() {
+    let (square, blobs) = square();
+    let proof = square.namespace_proof(&namespace(0x20));
+    assert!(verifies(&square, &proof));
+
+    let shares = proof.shares().cloned().collect::<Vec<_>>();
+    let expected = blobs[1..3]
+        .iter()
+        .flat_map(Blob::to_shares)
+        .map(Bytes::from)
+        .collect::<Vec<_>>();
+    assert_eq!(shares, expected);
+    assert!(
+        proof
+            .rows
+            .iter()
+            .filter(|row| !row.shares.is_empty())
+            .count()
+            > 1
+    );
+}
+
+#[test]
+fn truncated_namespace_fails() {
+    let (square, _) = square();
+    let proof = square.namespace_proof(&namespace(0x20));
+
+    // a share missing at the end of a row
+    let mut truncated = proof.clone();
+    let row = truncated
+        .rows
+        .iter_mut()
+        .find(|row| row.shares.len() > 1)
+        .unwrap();
+    row.shares.pop();
+    assert!(!verifies(&square, &truncated));
+
+    // the row before or after the namespace missing, the namespace starts in row 1
+    let mut truncated = proof.clone();
+    truncated.rows.remove(0);
+    assert!(!verifies(&square, &truncated));
+    let mut truncated = proof.clone();
+    truncated.rows.pop();
+    assert!(!verifies(&square, &truncated));
+
+    // a row of the namespace missing
+    let mut truncated = proof;
+    let index = truncated
+        .rows
+        .iter()
+        .rposition(|row| !row.shares.is_empty())
+        .unwrap();
+    truncated.rows.truncate(index);
+    assert!(!verifies(&square, &truncated));
+}
+
+#[test]
+fn forged_leaf_count_fails() {
+    let (square, _) = square();
+    let proof = square.namespace_proof(&namespace(0x20));
+    // with one more leaf than the half of the data root, the rows of the first half keep their
+    // paths, so their proofs still verify while the square would end early
+    let leaves = proof.rows[0].root_proof.numLeaves / U256::from(2) + U256::from(1);
+    let mut forged = proof;
+    for row in &mut forged.rows {
+        row.root_proof.numLeaves = leaves;
+        assert!(native::binary_merkle::verify(
+            &square.data_root(),
+            &row.root_proof,
+            &row.root.to_bytes()
+        ));
+    }
+    assert!(!verifies(&square, &forged));
+}
+
+#[test]
+fn proof_round_trips_through_serde() -> eyre::Result<()> {
+    let (square, _) = square();
+    for id in [0x20, 0x28] {
+        let proof = square.namespace_proof(&namespace(id));
+        let decoded: NamespaceProof = serde_json::from_slice(&serde_json::to_vec(&proof)?)?;
+        assert_eq!(decoded, proof);
+    }
+    Ok(())
+}
+
+#[test]
+fn absent_namespace_verifies() {
+    let (square, _) = square();
+    for id in [0x01, 0x18, 0x28] {
+        let proof = square.namespace_proof(&namespace(id));
+        assert!(verifies(&square, &proof), "namespace {id:#x}");
+        assert_eq!(proof.shares().count(), 0);
+    }
+}
+
+#[test]
+fn absence_of_present_namespace_fails() {
+    let (square, _) = square();
+    for (absent, present) in [(0x18, 0x10), (0x28, 0x30), (0x18, 0x20)] {
+        let mut proof = square.namespace_proof(&namespace(absent));
+        proof.namespace = namespace(present);
+        assert!(!verifies(&square, &proof), "namespace {present:#x}");
+    }
+}
+
+#[test]
+fn namespace_of_attested_block_validates() -> eyre::Result<()> {
+    let (square, _) = square();
+    let data_roots = [B256::repeat_byte(0x01), square.data_root()];
+    let commitment = DataCommitment::new(HEIGHT - 1, data_roots);
+    let attestation = commitment.attestation_proof(HEIGHT, U256::from(1)).unwrap();
+    let input = GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof()?,
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: square.shares_proof(1, attestation),
+    };
+
+    let proof = square.namespace_proof(&namespace(0x20));
+    let outputs =
+        guest::validate_namespace(input.clone(), &proof, Backend::Both, Sp1Verifier::Stub)?;
+    assert_eq!(
+        outputs.abi_encode(),
+        NamespaceOutputs {
+            inputCommit: keccak256(&input.abi_encode()[4..]),
+            namespace: namespace(0x20).to_bytes().into(),
+            dataRoot: square.data_root(),
+            sharesDigest: proof.shares_digest(),
+        }
+        .abi_encode()
+    );
+
+    let other = ExtendedSquare::new(&[Blob {
+        namespace: namespace(0x20),
+        data: vec![0x22; 100],
+    }]);
+    let proof = other.namespace_proof(&namespace(0x20));
+    let err =
+        guest::validate_namespace(input, &proof, Backend::Both, Sp1Verifier::Stub).unwrap_err();
+    assert!(
+        err.to_string().contains("incomplete namespace proof"),
+        "{err}"
+    );
+    Ok(())
+}
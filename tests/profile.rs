@@ -0,0 +1,81 @@
+//! Tests of the stage reports of both backends, with a stubbed SP1 verifier.
+//!
+//! Run with `cargo test --features test-utils`.
+#![cfg(feature = "test-utils")]
+use alloy_primitives::{Address, B256, U256};
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Sp1Verifier},
+    native::shares::Blob,
+    profile::Stage,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::Namespace,
+};
+
+const SHA256: Address = Address::with_last_byte(2);
+
+fn synthetic_input() -> GuestInput {
+    let square = ExtendedSquare::new(&[Blob {
+        namespace: Namespace::new(0, &[0x01; 28]).unwrap(),
+        data: vec![0xab; 2_000],
+    }]);
+    let commitment = DataCommitment::new(100, [B256::ZERO, square.data_root(), B256::ZERO]);
+    let attestation = commitment.attestation_proof(101, U256::from(7)).unwrap();
+    GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof().unwrap(),
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: square.shares_proof(0, attestation),
+    }
+}
+
+#[test]
+fn backends_reach_the_same_stages() {
+    let input = synthetic_input();
+    for backend in [Backend::Evm, Backend::Native, Backend::Both] {
+        let (result, report) =
+            guest::profile_with(input.clone(), backend, Sp1Verifier::Stub, None).unwrap();
+        result.unwrap();
+        assert_eq!(report.reached, Stage::CommitHashing, "{backend:?}");
+
+        for stage in Stage::ALL.into_iter().skip(1) {
+            let (result, report) =
+                guest::profile_with(input.clone(), backend, Sp1Verifier::Stub, Some(stage))
+                    .unwrap();
+            let err = result.unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("stopped before stage {}", stage.name())
+            );
+            let previous = Stage::ALL[stage as usize - 1];
+            assert_eq!(
+                report.reached, previous,
+                "{backend:?} stopped before {stage:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn evm_stages_count_their_hashes() {
+    let (result, report) =
+        guest::profile_with(synthetic_input(), Backend::Evm, Sp1Verifier::Stub, None).unwrap();
+    result.unwrap();
+    for stage in [
+        Stage::Sp1Proof,
+        Stage::Attestation,
+        Stage::RowProofs,
+        Stage::NmtProofs,
+    ] {
+        assert!(
+            report.precompile_calls.contains_key(&(stage, SHA256)),
+            "no sha256 call in {stage:?}: {:?}",
+            report.precompile_calls
+        );
+    }
+    assert!(
+        !report
+            .precompile_calls
+            .keys()
+            .any(|(stage, _)| matches!(stage, Stage::Setup | Stage::CommitHashing))
+    );
+}
@@ -0,0 +1,102 @@
+//! Tests of the retries and failover of the host RPC calls.
+//!
+//! Run with `cargo test --features mock-rpc`.
+#![cfg(feature = "mock-rpc")]
+
+use alloy_primitives::B256;
+use openvm_blobstream::host::{
+    mock::{CelestiaExchange, MockRpc, RpcFixture},
+    retry::{Failover, RetryPolicy, Timeout},
+    source::{CelestiaSource, CometBftSource},
+};
+use serde_json::json;
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy {
+        timeout: Duration::from_millis(200),
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(4),
+    }
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() -> eyre::Result<()> {
+    let attempts = AtomicU32::new(0);
+    let result = fast_policy()
+        .run(|| async {
+            if attempts.fetch_add(1, Ordering::Relaxed) < 2 {
+                Err(Timeout(Duration::ZERO).into())
+            } else {
+                Ok(42)
+            }
+        })
+        .await?;
+    assert_eq!((result, attempts.into_inner()), (42, 3));
+    Ok(())
+}
+
+#[tokio::test]
+async fn retries_are_bounded() {
+    let attempts = AtomicU32::new(0);
+    let result: eyre::Result<()> = fast_policy()
+        .run(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(())
+        })
+        .await;
+    assert!(result.unwrap_err().is::<Timeout>());
+    assert_eq!(attempts.into_inner(), 3);
+}
+
+#[tokio::test]
+async fn permanent_errors_are_not_retried() {
+    let attempts = AtomicU32::new(0);
+    let result: eyre::Result<()> = fast_policy()
+        .run(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(eyre::eyre!("tx not found"))
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.into_inner(), 1);
+}
+
+#[tokio::test]
+async fn failover_moves_to_the_next_endpoint() -> eyre::Result<()> {
+    let data_hash = B256::repeat_byte(0x0d);
+    let server = MockRpc::serve(RpcFixture {
+        celestia: vec![CelestiaExchange {
+            path: "block".to_string(),
+            query: [("height".to_string(), "7".to_string())].into(),
+            response: json!({ "result": { "block": { "header": {
+                "data_hash": alloy_primitives::hex::encode_upper(data_hash),
+            }}}}),
+        }],
+        ethereum: Vec::new(),
+        celestia_node: Vec::new(),
+    })
+    .await?;
+    // nothing listens on the first endpoint
+    let dead = MockRpc::serve(RpcFixture::default()).await?;
+    let dead_url = dead.celestia_url();
+    drop(dead);
+
+    let mut sources = Failover::new(vec![
+        CometBftSource { url: dead_url },
+        CometBftSource {
+            url: server.celestia_url(),
+        },
+    ]);
+    sources.policy = fast_policy();
+    assert_eq!(sources.get_block_data_hash(7).await?, data_hash);
+
+    let err = sources.get_block_data_hash(8).await.unwrap_err();
+    assert!(err.to_string().starts_with("all sources failed"), "{err}");
+    Ok(())
+}
@@ -0,0 +1,374 @@
+//! End-to-end tests of the host pipeline and `guest::validate` against mock RPC servers.
+//!
+//! Every `tests/fixtures/rpc/<name>/case.json` is replayed by `recorded_fixtures_validate`, with the
+//! `recorded-fixtures` feature as the fixtures are not checked in until they are recorded. New
+//! cases are recorded from live endpoints with
+//!
+//! ```sh
+//! RECORD_FIXTURE=<name> CELESTIA_TX_HASH=<hash> \
+//!     CELESTIA_RPC_URL=https://celestia-rpc.publicnode.com:443 \
+//!     ETHEREUM_RPC_URL=https://ethereum-rpc.publicnode.com \
+//!     cargo test --features mock-rpc --test rpc -- --ignored record_fixture
+//! ```
+//!
+//! Mocha cases are recorded from Mocha endpoints with `BLOBSTREAM_ADDRESS` set to the Sepolia
+//! deployment.
+//!
+//! Run with `cargo test --features mock-rpc`.
+#![cfg(feature = "mock-rpc")]
+
+use alloy_primitives::{Address, B256, Bytes, U256, address, hex};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_sol_types::{SolCall, SolEvent, SolValue};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use openvm_blobstream::{
+    GuestInput,
+    da_oracle::{DataCommitmentStored, SP1Blobstream, commitHeaderRangeCall},
+    guest::{self, Backend},
+    host::{
+        self, get_celestia_block_data_hash,
+        mock::{CelestiaExchange, EthereumExchange, MockRpc, RpcFixture},
+        source::{CelestiaSource, CometBftSource},
+    },
+    native::{ProofOutputs, binary_merkle, nmt},
+    verifier::{DataRootTuple, Namespace},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+
+const MAINNET_BLOBSTREAM: Address = address!("0x7Cf3876F681Dbb6EdA8f6FfC45D66B996Df08fAe");
+
+/// A recorded run of the pipeline.
+#[derive(Debug, Serialize, Deserialize)]
+struct Case {
+    celestia_tx_hash: B256,
+    blobstream: Address,
+    rpc: RpcFixture,
+}
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/rpc")
+}
+
+/// Fetches everything the guest needs from the mock servers, as `openvm/script` does.
+async fn fetch_guest_input(
+    celestia_url: &str,
+    ethereum_url: &str,
+    blobstream: Address,
+    tx_hash: B256,
+) -> eyre::Result<GuestInput> {
+    let ethereum_client = ProviderBuilder::new().connect_http(ethereum_url.parse()?);
+    let celestia = CometBftSource {
+        url: celestia_url.to_string(),
+    };
+    host::build_guest_input(
+        &celestia,
+        &SP1Blobstream::new(blobstream, ethereum_client),
+        tx_hash,
+    )
+    .await
+}
+
+#[cfg(feature = "recorded-fixtures")]
+#[tokio::test]
+async fn recorded_fixtures_validate() -> eyre::Result<()> {
+    use eyre::{WrapErr, ensure};
+
+    let dir = fixtures_dir();
+    let mut cases = std::fs::read_dir(&dir)
+        .wrap_err_with(|| format!("no recorded fixtures in {}", dir.display()))?
+        .map(|entry| Ok(entry?.path().join("case.json")))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    ensure!(
+        !cases.is_empty(),
+        "no recorded fixtures in {}, record one with `record_fixture`",
+        dir.display()
+    );
+    cases.sort();
+    for path in cases {
+        let case: Case = serde_json::from_slice(&std::fs::read(&path)?)?;
+        let server = MockRpc::serve(case.rpc).await?;
+        let input = fetch_guest_input(
+            &server.celestia_url(),
+            &server.ethereum_url(),
+            case.blobstream,
+            case.celestia_tx_hash,
+        )
+        .await?;
+        guest::validate_with_backend(input, Backend::Both)
+            .map_err(|e| e.wrap_err(format!("{}", path.display())))?;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "records a fixture from live endpoints"]
+async fn record_fixture() -> eyre::Result<()> {
+    let var = |name: &str| std::env::var(name).map_err(|_| eyre::eyre!("{name} is not set"));
+    let name = var("RECORD_FIXTURE")?;
+    let celestia_tx_hash: B256 = var("CELESTIA_TX_HASH")?.parse()?;
+    let blobstream = match var("BLOBSTREAM_ADDRESS") {
+        Ok(address) => address.parse()?,
+        Err(_) => MAINNET_BLOBSTREAM,
+    };
+
+    let server = MockRpc::record(&var("CELESTIA_RPC_URL")?, &var("ETHEREUM_RPC_URL")?).await?;
+    let input = fetch_guest_input(
+        &server.celestia_url(),
+        &server.ethereum_url(),
+        blobstream,
+        celestia_tx_hash,
+    )
+    .await?;
+    guest::validate(input.clone())?;
+
+    let case = Case {
+        celestia_tx_hash,
+        blobstream,
+        rpc: server.fixture(),
+    };
+    let dir = fixtures_dir().join(&name);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("case.json"), serde_json::to_vec_pretty(&case)?)?;
+
+    // the assembled input is also a golden input of `tests/fixtures.rs`
+    let inputs_dir = fixtures_dir().with_file_name("inputs");
+    std::fs::create_dir_all(&inputs_dir)?;
+    std::fs::write(
+        inputs_dir.join(format!("{name}.json")),
+        serde_json::to_vec_pretty(&input)?,
+    )?;
+    Ok(())
+}
+
+const HEIGHT: u64 = 1_000;
+const BLOCK_HASH: B256 = B256::repeat_byte(0xbb);
+
+/// A case with a single share in a single row, whose data commitment covers a single block.
+///
+/// The SP1 proof is not a valid proof, so only the merkle proofs of the input hold.
+fn synthetic_case() -> Case {
+    let namespace = Namespace::new(0, &[[0u8; 18].as_slice(), &[0x01; 10]].concat()).unwrap();
+    let share = [namespace.to_bytes().as_slice(), &[0x01], &[0xab; 482]].concat();
+    let row_root = nmt::leaf_digest(&namespace, &share);
+    let data_root = binary_merkle::compute_root_from_leaves(&[row_root.to_bytes()]);
+    let tuple = DataRootTuple {
+        height: U256::from(HEIGHT),
+        dataRoot: data_root,
+    };
+    let data_commitment = binary_merkle::compute_root_from_leaves(&[tuple.abi_encode()]);
+
+    let public_values = ProofOutputs {
+        trustedHeaderHash: B256::ZERO,
+        targetHeaderHash: B256::ZERO,
+        dataCommitment: data_commitment,
+        trustedBlock: HEIGHT,
+        targetBlock: HEIGHT + 1,
+        validatorBitmap: U256::ZERO,
+    };
+    let commit_call = commitHeaderRangeCall {
+        proof: Bytes::from_static(&[0; 4]),
+        publicValues: public_values.abi_encode().into(),
+    };
+
+    let celestia_tx_hash = B256::repeat_byte(0xab);
+    let commit_tx_hash = B256::repeat_byte(0xcd);
+    let blobstream = MAINNET_BLOBSTREAM;
+    let single_leaf_proof = json!({ "total": "1", "index": "0", "aunts": [] });
+
+    let celestia = vec![
+        CelestiaExchange {
+            path: "tx".to_string(),
+            query: [("hash".to_string(), hex::encode_prefixed(celestia_tx_hash))].into(),
+            response: json!({ "result": {
+                "height": HEIGHT.to_string(),
+                "proof": {
+                    "data": [BASE64.encode(&share)],
+                    "share_proofs": [{ "end": 1, "nodes": [] }],
+                    "namespace_id": BASE64.encode(namespace.id),
+                    "row_proof": {
+                        "row_roots": [hex::encode_upper(row_root.to_bytes())],
+                        "proofs": [single_leaf_proof],
+                    },
+                    "namespace_version": 0,
+                },
+            }}),
+        },
+        CelestiaExchange {
+            path: "block".to_string(),
+            query: [("height".to_string(), HEIGHT.to_string())].into(),
+            response: json!({ "result": {
+                "block_id": { "hash": hex::encode_upper(BLOCK_HASH) },
+                "block": { "header": {
+                    "height": HEIGHT.to_string(),
+                    "data_hash": hex::encode_upper(data_root),
+                }},
+            }}),
+        },
+        CelestiaExchange {
+            path: "data_root_inclusion_proof".to_string(),
+            query: [
+                ("height".to_string(), HEIGHT.to_string()),
+                ("start".to_string(), HEIGHT.to_string()),
+                ("end".to_string(), (HEIGHT + 1).to_string()),
+            ]
+            .into(),
+            response: json!({ "result": { "proof": single_leaf_proof } }),
+        },
+    ];
+
+    let eth_block = 0x100000u64;
+    let ethereum = vec![
+        EthereumExchange {
+            method: "eth_blockNumber".to_string(),
+            params: None,
+            result: json!(format!("{eth_block:#x}")),
+        },
+        EthereumExchange {
+            method: "eth_getLogs".to_string(),
+            params: None,
+            result: json!([{
+                "address": blobstream,
+                "topics": [
+                    DataCommitmentStored::SIGNATURE_HASH,
+                    B256::from(U256::from(HEIGHT)),
+                    B256::from(U256::from(HEIGHT + 1)),
+                    data_commitment,
+                ],
+                "data": hex::encode_prefixed(U256::from(1).abi_encode()),
+                "blockHash": B256::repeat_byte(0x01),
+                "blockNumber": format!("{eth_block:#x}"),
+                "transactionHash": commit_tx_hash,
+                "transactionIndex": "0x0",
+                "logIndex": "0x0",
+                "removed": false,
+            }]),
+        },
+        EthereumExchange {
+            method: "eth_getTransactionByHash".to_string(),
+            params: None,
+            result: json!({
+                "type": "0x0",
+                "chainId": "0x1",
+                "nonce": "0x0",
+                "gasPrice": "0x1",
+                "gas": "0x100000",
+                "to": blobstream,
+                "value": "0x0",
+                "input": hex::encode_prefixed(commit_call.abi_encode()),
+                "r": "0x1",
+                "s": "0x1",
+                "v": "0x25",
+                "hash": commit_tx_hash,
+                "blockHash": B256::repeat_byte(0x01),
+                "blockNumber": format!("{eth_block:#x}"),
+                "transactionIndex": "0x0",
+                "from": Address::repeat_byte(0x11),
+            }),
+        },
+    ];
+
+    Case {
+        celestia_tx_hash,
+        blobstream,
+        rpc: RpcFixture {
+            celestia,
+            ethereum,
+            celestia_node: Vec::new(),
+        },
+    }
+}
+
+#[tokio::test]
+async fn synthetic_fixture_runs_the_pipeline() -> eyre::Result<()> {
+    let case = synthetic_case();
+    let server = MockRpc::serve(case.rpc).await?;
+    let input = fetch_guest_input(
+        &server.celestia_url(),
+        &server.ethereum_url(),
+        case.blobstream,
+        case.celestia_tx_hash,
+    )
+    .await?;
+
+    // the merkle proofs fetched from the mock servers hold
+    let shares = &input.sharesProof;
+    let attestation = &shares.attestationProof;
+    let outputs = ProofOutputs::abi_decode(&input.commitHeaderRangePublicValues)?;
+    assert!(binary_merkle::verify(
+        &outputs.dataCommitment,
+        &attestation.proof,
+        &attestation.tuple.abi_encode(),
+    ));
+    assert!(binary_merkle::verify(
+        &attestation.tuple.dataRoot,
+        &shares.rowProofs[0],
+        &shares.rowRoots[0].to_bytes(),
+    ));
+    assert!(nmt::verify_multi(
+        &shares.rowRoots[0],
+        &shares.shareProofs[0],
+        &shares.namespace,
+        &shares.data,
+    ));
+
+    // but the SP1 proof does not
+    let err = guest::validate_with_backend(input.clone(), Backend::Native).unwrap_err();
+    assert_eq!(err.to_string(), "invalid commitHeaderRange proof");
+    assert!(guest::validate_with_backend(input, Backend::Evm).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn fixture_sources_match_the_servers() -> eyre::Result<()> {
+    let case = synthetic_case();
+    let server = MockRpc::serve(case.rpc.clone()).await?;
+    let served = fetch_guest_input(
+        &server.celestia_url(),
+        &server.ethereum_url(),
+        case.blobstream,
+        case.celestia_tx_hash,
+    )
+    .await?;
+    let replayed = host::build_guest_input(&case.rpc, &case.rpc, case.celestia_tx_hash).await?;
+    assert_eq!(served, replayed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn headers_match_between_sources() -> eyre::Result<()> {
+    let case = synthetic_case();
+    let server = MockRpc::serve(case.rpc.clone()).await?;
+    let celestia = CometBftSource {
+        url: server.celestia_url(),
+    };
+    let header = celestia.get_header(HEIGHT).await?;
+    assert_eq!((header.height, header.hash), (HEIGHT, BLOCK_HASH));
+    assert_eq!(
+        header.data_hash,
+        celestia.get_block_data_hash(HEIGHT).await?
+    );
+    assert_eq!(case.rpc.get_header(HEIGHT).await?, header);
+
+    let namespace = Namespace::new(0, &[0x01; 28])?;
+    let err = celestia
+        .get_namespace_shares(HEIGHT, &namespace)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("celestia-node"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn unrecorded_requests_fail() -> eyre::Result<()> {
+    let server = MockRpc::serve(RpcFixture::default()).await?;
+    assert!(
+        get_celestia_block_data_hash(&server.celestia_url(), HEIGHT)
+            .await
+            .is_err()
+    );
+    let provider = ProviderBuilder::new().connect_http(server.ethereum_url().parse()?);
+    assert!(provider.get_block_number().await.is_err());
+    Ok(())
+}
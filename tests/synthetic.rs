@@ -0,0 +1,212 @@
+//! Property tests of the verifiers on proofs of synthetic squares, with a stubbed SP1 verifier.
+//!
+//! The cases run through the EVM are fewer than the native ones, raise them with
+//! `PROPTEST_CASES`.
+//!
+//! Run with `cargo test --features test-utils`.
+#![cfg(feature = "test-utils")]
+use alloy_primitives::{B256, Bytes, U256, hex};
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, ExecutionMode, Sp1Verifier},
+    native,
+    native::shares::{Blob, TAIL_PADDING_NAMESPACE},
+    synthetic::{DataCommitment, ExtendedSquare, sp1_stub_proof},
+    verifier::Namespace,
+};
+use proptest::prelude::*;
+
+/// A block with the given blobs, within a data commitment of `len` blocks.
+#[derive(Debug, Clone)]
+struct Case {
+    blobs: Vec<Blob>,
+    /// The blob to prove.
+    index: usize,
+    start_block: u64,
+    len: u64,
+    /// Offset of the block in the data commitment.
+    offset: u64,
+}
+
+fn blob() -> impl Strategy<Value = Blob> {
+    (
+        any::<[u8; 10]>(),
+        prop::collection::vec(any::<u8>(), 1..2_000),
+    )
+        .prop_map(|(id, data)| Blob {
+            namespace: Namespace::new(0, &[[0u8; 18].as_slice(), &id].concat()).unwrap(),
+            data,
+        })
+}
+
+fn case() -> impl Strategy<Value = Case> {
+    (
+        prop::collection::vec(blob(), 1..6),
+        any::<prop::sample::Index>(),
+        1u64..1_000_000,
+        1u64..64,
+        any::<prop::sample::Index>(),
+    )
+        .prop_map(|(blobs, index, start_block, len, offset)| Case {
+            index: index.index(blobs.len()),
+            blobs,
+            start_block,
+            len,
+            offset: offset.index(len as usize) as u64,
+        })
+}
+
+/// Builds the input proving the blob of the case, the other blocks of the commitment have
+/// arbitrary data roots.
+fn guest_input(case: &Case) -> GuestInput {
+    let square = ExtendedSquare::new(&case.blobs);
+    let height = case.start_block + case.offset;
+    let data_roots = (case.start_block..case.start_block + case.len).map(|block| {
+        if block == height {
+            square.data_root()
+        } else {
+            B256::left_padding_from(&block.to_be_bytes())
+        }
+    });
+    let commitment = DataCommitment::new(case.start_block, data_roots);
+    let attestation = commitment.attestation_proof(height, U256::from(1)).unwrap();
+
+    GuestInput {
+        commitHeaderRangeProof: sp1_stub_proof().unwrap(),
+        commitHeaderRangePublicValues: commitment.public_values(),
+        sharesProof: square.shares_proof(case.index, attestation),
+    }
+}
+
+fn flip_byte(bytes: &Bytes, index: usize) -> Bytes {
+    let mut flipped = bytes.to_vec();
+    flipped[index % bytes.len()] ^= 0x01;
+    flipped.into()
+}
+
+/// Corrupts one part of the proof, `selector` picks the corruption and the part.
+fn corrupt(input: &mut GuestInput, corruption: u8, selector: prop::sample::Index) {
+    let proof = &mut input.sharesProof;
+    match corruption {
+        0 => {
+            let share = selector.get_mut(&mut proof.data);
+            *share = flip_byte(share, selector.index(512));
+        }
+        1 => {
+            let root = selector.get_mut(&mut proof.rowRoots);
+            root.digest = !root.digest;
+        }
+        2 => {
+            let share_proof = selector.get_mut(&mut proof.shareProofs);
+            share_proof.endKey += U256::from(1);
+        }
+        3 => {
+            let row_proof = selector.get_mut(&mut proof.rowProofs);
+            row_proof.key += U256::from(1);
+        }
+        4 => proof.attestationProof.tuple.dataRoot = !proof.attestationProof.tuple.dataRoot,
+        5 => proof.attestationProof.proof.key += U256::from(1),
+        6 => {
+            proof.data.pop();
+        }
+        7 => {
+            let share_proof = selector.get_mut(&mut proof.shareProofs);
+            share_proof.beginKey = share_proof.endKey;
+        }
+        _ => {
+            // the range ends before it begins, which underflows in the contract
+            let share_proof = selector.get_mut(&mut proof.shareProofs);
+            share_proof.beginKey = share_proof.endKey + U256::from(1);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn native_accepts_valid_proofs(case in case()) {
+        let input = guest_input(&case);
+        prop_assert!(native::verify_with(&input, Sp1Verifier::Stub).is_ok());
+    }
+
+    #[test]
+    fn native_rejects_corrupted_proofs(
+        case in case(),
+        corruption in 0u8..9,
+        selector in any::<prop::sample::Index>(),
+    ) {
+        let mut input = guest_input(&case);
+        corrupt(&mut input, corruption, selector);
+        prop_assert!(native::verify_with(&input, Sp1Verifier::Stub).is_err());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn backends_accept_valid_proofs(case in case()) {
+        let input = guest_input(&case);
+        let result = guest::validate_with(input, Backend::Both, Sp1Verifier::Stub);
+        prop_assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn backends_agree_on_corrupted_proofs(
+        case in case(),
+        corruption in 0u8..9,
+        selector in any::<prop::sample::Index>(),
+    ) {
+        let mut input = guest_input(&case);
+        corrupt(&mut input, corruption, selector);
+        let result = guest::validate_with(input, Backend::Both, Sp1Verifier::Stub);
+        let err = result.expect_err("corrupted proof accepted");
+        prop_assert!(!err.to_string().starts_with("backend mismatch"), "{err}");
+    }
+
+    #[test]
+    fn execution_modes_agree(
+        case in case(),
+        corrupted in any::<bool>(),
+        corruption in 0u8..9,
+        selector in any::<prop::sample::Index>(),
+    ) {
+        let mut input = guest_input(&case);
+        if corrupted {
+            corrupt(&mut input, corruption, selector);
+        }
+        let lean = guest::validate_in_mode(input.clone(), ExecutionMode::Lean, Sp1Verifier::Stub);
+        let mainnet = guest::validate_in_mode(input, ExecutionMode::Mainnet, Sp1Verifier::Stub);
+        prop_assert_eq!(lean.is_ok(), !corrupted, "{:?}", lean);
+        prop_assert_eq!(
+            lean.map_err(|e| e.to_string()),
+            mainnet.map_err(|e| e.to_string())
+        );
+    }
+}
+
+#[test]
+fn real_sp1_verifier_rejects_the_stub_proof() {
+    let case = Case {
+        blobs: vec![Blob {
+            namespace: Namespace::new(0, &[0x01; 28]).unwrap(),
+            data: vec![0xab; 100],
+        }],
+        index: 0,
+        start_block: 10,
+        len: 4,
+        offset: 2,
+    };
+    let input = guest_input(&case);
+    assert!(guest::validate_with(input.clone(), Backend::Both, Sp1Verifier::Stub).is_ok());
+    let err = guest::validate_with_backend(input, Backend::Both).unwrap_err();
+    assert!(!err.to_string().starts_with("backend mismatch"), "{err}");
+}
+
+#[test]
+fn tail_padding_namespace_is_the_one_of_celestia_app() {
+    // `share.TailPaddingNamespace.Bytes()` of go-square
+    assert_eq!(
+        TAIL_PADDING_NAMESPACE.to_bytes(),
+        hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffe")
+    );
+}
@@ -0,0 +1,102 @@
+//! Tests of the mock SP1 verifier, with an injected program key.
+//!
+//! Run with `cargo test --features test-verifier`.
+#![cfg(feature = "test-verifier")]
+
+use alloy_primitives::{B256, U256};
+use openvm_blobstream::{
+    GuestInput,
+    guest::{self, Backend, Sp1Verifier},
+    native::shares::Blob,
+    synthetic::{DataCommitment, ExtendedSquare, sp1_mock_proof, sp1_stub_proof},
+    verifier::{Namespace, SP1_BLOB_STREAM_PROGRAM_VKEY},
+};
+
+const VKEY: B256 = B256::repeat_byte(0x5a);
+
+fn synthetic_input() -> GuestInput {
+    let square = ExtendedSquare::new(&[Blob {
+        namespace: Namespace::new(0, &[0x01; 28]).unwrap(),
+        data: vec![0xab; 1_000],
+    }]);
+    let commitment = DataCommitment::new(100, [B256::ZERO, square.data_root(), B256::ZERO]);
+    let attestation = commitment.attestation_proof(101, U256::from(7)).unwrap();
+    let public_values = commitment.public_values();
+    GuestInput {
+        commitHeaderRangeProof: sp1_mock_proof(VKEY, &public_values).unwrap(),
+        commitHeaderRangePublicValues: public_values,
+        sharesProof: square.shares_proof(0, attestation),
+    }
+}
+
+#[test]
+fn program_vkey_is_injected() {
+    assert_eq!(
+        guest::program_vkey(Sp1Verifier::Plonk).unwrap(),
+        SP1_BLOB_STREAM_PROGRAM_VKEY
+    );
+    for vkey in [VKEY, SP1_BLOB_STREAM_PROGRAM_VKEY, B256::ZERO] {
+        assert_eq!(
+            guest::program_vkey(Sp1Verifier::Mock { vkey }).unwrap(),
+            vkey
+        );
+    }
+}
+
+#[test]
+fn mock_verifier_accepts_synthetic_inputs() {
+    let input = synthetic_input();
+    guest::validate_with(
+        input.clone(),
+        Backend::Both,
+        Sp1Verifier::Mock { vkey: VKEY },
+    )
+    .unwrap();
+    assert!(guest::validate_with_backend(input, Backend::Evm).is_err());
+}
+
+#[test]
+fn mock_verifier_checks_the_program_key() {
+    let input = synthetic_input();
+    for vkey in [SP1_BLOB_STREAM_PROGRAM_VKEY, B256::ZERO] {
+        let err = guest::validate_with(input.clone(), Backend::Both, Sp1Verifier::Mock { vkey })
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("invalid commitHeaderRange proof"),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn mock_verifier_checks_the_public_values() {
+    let mut input = synthetic_input();
+    // the proof of the public values of another data commitment
+    let other = DataCommitment::new(100, [B256::ZERO, B256::ZERO, B256::ZERO]).public_values();
+    input.commitHeaderRangeProof = sp1_mock_proof(VKEY, &other).unwrap();
+    let err = guest::validate_with(
+        input.clone(),
+        Backend::Both,
+        Sp1Verifier::Mock { vkey: VKEY },
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid commitHeaderRange proof"),
+        "{err}"
+    );
+
+    input.commitHeaderRangeProof = sp1_stub_proof().unwrap();
+    assert!(guest::validate_with(input, Backend::Both, Sp1Verifier::Mock { vkey: VKEY }).is_err());
+}
+
+#[test]
+fn mock_verifier_still_checks_the_merkle_proofs() {
+    let mut input = synthetic_input();
+    input.sharesProof.attestationProof.tuple.height += U256::from(1);
+    let err =
+        guest::validate_with(input, Backend::Both, Sp1Verifier::Mock { vkey: VKEY }).unwrap_err();
+    assert!(
+        err.to_string().contains("invalid BinaryMerkleTree proof"),
+        "{err}"
+    );
+}
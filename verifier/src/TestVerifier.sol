@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// solhint-disable gas-custom-errors
+pragma solidity ^0.8.30;
+
+import {DAVerifier, SharesProof} from "verifier/DAVerifier.sol";
+import {IDAOracle} from "blobstream-contracts/IDAOracle.sol";
+import {DataRootTuple} from "blobstream-contracts/DataRootTuple.sol";
+import {BinaryMerkleProof} from "tree/binary/BinaryMerkleProof.sol";
+import {BinaryMerkleTree} from "tree/binary/BinaryMerkleTree.sol";
+
+/// Verifier.sol with a mock SP1 verifier and the program key given to the constructor, for tests
+/// and devnets only.
+///
+/// The mock accepts the proofs `VERIFIER_HASH()[:4] || abi.encode(programVKey, publicValuesDigest)`.
+contract TestVerifier is IDAOracle {
+    uint64 public constant DATA_COMMITMENT_MAX = 10000;
+    bytes32 public immutable SP1_BLOB_STREAM_PROGRAM_VK;
+
+    bytes32 internal root;
+    uint256 internal tupleRootNonce;
+
+    struct ProofOutputs {
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        bytes32 dataCommitment;
+        uint64 trustedBlock;
+        uint64 targetBlock;
+        uint256 validatorBitmap;
+    }
+
+    error WrongVerifierSelector(bytes4 received, bytes4 expected);
+
+    constructor(bytes32 programVKey) {
+        SP1_BLOB_STREAM_PROGRAM_VK = programVKey;
+    }
+
+    function verify(
+        bytes calldata commitHeaderRangeProof,
+        bytes calldata commitHeaderRangePublicValues,
+        SharesProof calldata sharesProof
+    ) external returns (bytes32 commit) {
+        ProofOutputs memory po = abi.decode(commitHeaderRangePublicValues, (ProofOutputs));
+
+        uint64 blockHeight = uint64(sharesProof.attestationProof.tuple.height);
+        uint64 startBlock = po.trustedBlock;
+        uint64 endBlock = po.targetBlock;
+
+        require(endBlock - startBlock <= DATA_COMMITMENT_MAX, "proof block range too large");
+        require(blockHeight >= startBlock && blockHeight <= endBlock, "attested block not in range");
+
+        root = po.dataCommitment;
+        tupleRootNonce = sharesProof.attestationProof.tupleRootNonce;
+
+        verifyBlobStreamProof(commitHeaderRangePublicValues, commitHeaderRangeProof);
+
+        (bool committedTo,) = DAVerifier.verifySharesToDataRootTupleRoot(this, sharesProof);
+        require(committedTo, "invalid sharesProof");
+
+        // hash inputs
+        assembly {
+            let cds := calldatasize()
+            let n := sub(cds, 4)
+            calldatacopy(0x00, 4, n)
+            commit := keccak256(0x00, n)
+        }
+    }
+
+    function verifyAttestation(uint256 _tupleRootNonce, DataRootTuple memory tuple, BinaryMerkleProof memory proof)
+        external
+        view
+        returns (bool)
+    {
+        require(_tupleRootNonce == tupleRootNonce, "invalid nonce");
+
+        (bool isProofValid,) = BinaryMerkleTree.verify(root, proof, abi.encode(tuple));
+
+        require(isProofValid, "invalid BinaryMerkleTree proof");
+        return isProofValid;
+    }
+
+    /// The hash of the SP1 v5.0.0 Plonk verifier, so that the proofs keep their selector.
+    function VERIFIER_HASH() public pure returns (bytes32) {
+        return 0xd4e8ecd2357dd882209800acd6abb443d231cf287d77ba62b732ce937c8b56e7;
+    }
+
+    function hashPublicValues(bytes calldata publicValues) public pure returns (bytes32) {
+        return sha256(publicValues) & bytes32(uint256((1 << 253) - 1));
+    }
+
+    /// The mock SP1 verifier, called as the Plonk one of Verifier.sol.
+    function Verify(bytes calldata proof, uint256[] calldata public_inputs) public pure returns (bool) {
+        return public_inputs.length == 2 && keccak256(proof) == keccak256(abi.encode(public_inputs[0], public_inputs[1]));
+    }
+
+    function verifyBlobStreamProof(bytes calldata publicValues, bytes calldata proofBytes) internal view {
+        bytes4 receivedSelector = bytes4(proofBytes[:4]);
+        bytes4 expectedSelector = bytes4(VERIFIER_HASH());
+        if (receivedSelector != expectedSelector) {
+            revert WrongVerifierSelector(receivedSelector, expectedSelector);
+        }
+
+        bytes32 publicValuesDigest = hashPublicValues(publicValues);
+        uint256[] memory inputs = new uint256[](2);
+        inputs[0] = uint256(SP1_BLOB_STREAM_PROGRAM_VK);
+        inputs[1] = uint256(publicValuesDigest);
+        bool success = this.Verify(proofBytes[4:], inputs);
+        require(success, "invalid commitHeaderRange proof");
+    }
+}